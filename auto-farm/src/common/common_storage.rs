@@ -4,18 +4,31 @@ pub const MAX_PERCENTAGE: u64 = 10_000;
 
 #[multiversx_sc::module]
 pub trait CommonStorageModule {
-    fn require_caller_proxy_claim_address(&self) {
+    fn require_caller_keeper(&self) {
         let caller = self.blockchain().get_caller();
-        let proxy_claim_address = self.proxy_claim_address().get();
         require!(
-            caller == proxy_claim_address,
-            "Only the proxy can claim in user's place"
+            self.keepers().contains(&caller),
+            "Only keepers can claim in user's place"
+        );
+    }
+
+    fn require_caller_treasury(&self) {
+        let caller = self.blockchain().get_caller();
+        let treasury_address = self.treasury_address().get();
+        require!(
+            caller == treasury_address,
+            "Only the treasury can claim fees"
         );
     }
 
     #[storage_mapper("userIds")]
     fn user_ids(&self) -> AddressToIdMapper<Self::Api>;
 
-    #[storage_mapper("proxyClaimAddress")]
-    fn proxy_claim_address(&self) -> SingleValueMapper<ManagedAddress>;
+    #[view(getKeepers)]
+    #[storage_mapper("keepers")]
+    fn keepers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getTreasuryAddress)]
+    #[storage_mapper("treasuryAddress")]
+    fn treasury_address(&self) -> SingleValueMapper<ManagedAddress>;
 }
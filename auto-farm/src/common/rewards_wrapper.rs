@@ -43,6 +43,11 @@ impl<M: ManagedTypeApi> RewardsWrapper<M> {
         }
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.locked_tokens.is_empty() && self.other_tokens.is_empty()
+    }
+
     #[inline]
     pub fn get_locked_token_id(&self) -> &TokenIdentifier<M> {
         &self.locked_token_id
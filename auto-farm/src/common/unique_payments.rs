@@ -39,6 +39,11 @@ impl<M: ManagedTypeApi> UniquePayments<M> {
         merged_payments
     }
 
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.payments.is_empty()
+    }
+
     pub fn add_payment(&mut self, new_payment: EsdtTokenPayment<M>) {
         if new_payment.amount == 0 {
             return;
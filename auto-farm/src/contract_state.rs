@@ -0,0 +1,46 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+/// Active must stay the first variant, as it is the default state
+#[type_abi]
+#[derive(TopEncode, TopDecode, PartialEq, Clone, Copy, Debug)]
+pub enum ContractState {
+    Active,
+    ProcessingPaused,
+    WithdrawOnly,
+}
+
+#[multiversx_sc::module]
+pub trait ContractStateModule:
+    permissions_module::PermissionsModule + crate::events::EventsModule
+{
+    /// Active: Everything works as usual.
+    /// ProcessingPaused: Deposits and user processing are disabled.
+    /// WithdrawOnly: Only withdrawals and claims work.
+    ///
+    /// Withdrawing all tokens and claiming rewards is always possible.
+    #[endpoint(setContractState)]
+    fn set_contract_state(&self, state: ContractState) {
+        self.require_caller_has_owner_or_admin_permissions();
+
+        self.contract_state().set(state);
+        self.emit_contract_state_change_event(state);
+    }
+
+    fn require_active(&self) {
+        let state = self.contract_state().get();
+        require!(state == ContractState::Active, "Contract is not active");
+    }
+
+    fn require_not_withdraw_only(&self) {
+        let state = self.contract_state().get();
+        require!(
+            state != ContractState::WithdrawOnly,
+            "Contract is in withdraw only mode"
+        );
+    }
+
+    #[view(getContractState)]
+    #[storage_mapper("contractState")]
+    fn contract_state(&self) -> SingleValueMapper<ContractState>;
+}
@@ -0,0 +1,254 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
+
+#[multiversx_sc::module]
+pub trait DepositCapsModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + utils::UtilsModule
+{
+    /// Caps are measured in farming tokens. For metastaking, that is the LP tokens in the LP farm.
+    /// total_cap limits the amount routed by auto-farm into the contract,
+    /// and user_cap limits the amount of each user. A cap of 0 means no limit.
+    ///
+    /// Deposits must fit under the caps, while compounding continues until the caps are reached,
+    /// so the last compounding may take the usage over the caps.
+    #[only_owner]
+    #[endpoint(setDepositCaps)]
+    fn set_deposit_caps(&self, sc_address: ManagedAddress, total_cap: BigUint, user_cap: BigUint) {
+        require!(
+            self.farm_ids().get_id(&sc_address) != NULL_ID
+                || self.metastaking_ids().get_id(&sc_address) != NULL_ID,
+            "Unknown farm or metastaking address"
+        );
+
+        self.total_deposit_cap(&sc_address).set(total_cap);
+        self.user_deposit_cap(&sc_address).set(user_cap);
+    }
+
+    /// Deposits made for another user must have at least min_amount farming tokens
+    /// in each position of the contract, so accounts cannot be filled with dust positions.
+    /// A min_amount of 0 means no limit.
+    #[only_owner]
+    #[endpoint(setMinDepositForAmount)]
+    fn set_min_deposit_for_amount(&self, sc_address: ManagedAddress, min_amount: BigUint) {
+        require!(
+            self.farm_ids().get_id(&sc_address) != NULL_ID
+                || self.metastaking_ids().get_id(&sc_address) != NULL_ID,
+            "Unknown farm or metastaking address"
+        );
+
+        self.min_deposit_for_amount(&sc_address).set(min_amount);
+    }
+
+    #[view(getDepositCaps)]
+    fn get_deposit_caps(&self, sc_address: ManagedAddress) -> MultiValue2<BigUint, BigUint> {
+        let total_cap = self.total_deposit_cap(&sc_address).get();
+        let user_cap = self.user_deposit_cap(&sc_address).get();
+
+        (total_cap, user_cap).into()
+    }
+
+    /// Returns the current usage and the cap
+    #[view(getTotalDepositUsage)]
+    fn get_total_deposit_usage(&self, sc_address: ManagedAddress) -> MultiValue2<BigUint, BigUint> {
+        let usage = self.total_deposit_usage(&sc_address).get();
+        let cap = self.total_deposit_cap(&sc_address).get();
+
+        (usage, cap).into()
+    }
+
+    /// Returns the user's current usage and the per-user cap
+    #[view(getUserDepositUsage)]
+    fn get_user_deposit_usage(
+        &self,
+        user: ManagedAddress,
+        sc_address: ManagedAddress,
+    ) -> MultiValue2<BigUint, BigUint> {
+        let user_id = self.user_ids().get_id(&user);
+        let usage = if user_id != NULL_ID {
+            self.user_deposit_usage(user_id, &sc_address).get()
+        } else {
+            BigUint::zero()
+        };
+        let cap = self.user_deposit_cap(&sc_address).get();
+
+        (usage, cap).into()
+    }
+
+    fn require_min_deposit_for_amount(&self, positions: &PaymentsVec<Self::Api>) {
+        for position in positions.iter() {
+            if let Some((sc_address, amount)) = self.get_position_usage(&position) {
+                let min_amount = self.min_deposit_for_amount(&sc_address).get();
+                require!(amount >= min_amount, "Deposit amount too low");
+            }
+        }
+    }
+
+    fn add_deposited_positions_usage(
+        &self,
+        user_id: AddressId,
+        positions: &PaymentsVec<Self::Api>,
+    ) {
+        for position in positions.iter() {
+            if let Some((sc_address, amount)) = self.get_position_usage(&position) {
+                self.add_total_usage(&sc_address, &amount, true);
+                self.add_user_usage(user_id, &sc_address, &amount, true);
+            }
+        }
+    }
+
+    /// Legacy positions were deposited before the usage was tracked.
+    /// Their usage is added when the user is migrated, without checking the caps.
+    fn add_migrated_positions_usage(&self, user_id: AddressId, positions: &PaymentsVec<Self::Api>) {
+        for position in positions.iter() {
+            if let Some((sc_address, amount)) = self.get_position_usage(&position) {
+                self.add_compounded_usage(user_id, &sc_address, &amount);
+            }
+        }
+    }
+
+    /// Must be called while the contract still holds the positions
+    fn remove_positions_usage(&self, user_id: AddressId, positions: &PaymentsVec<Self::Api>) {
+        for position in positions.iter() {
+            if let Some((sc_address, amount)) = self.get_position_usage(&position) {
+                self.remove_usage(user_id, &sc_address, &amount);
+            }
+        }
+    }
+
+    fn remove_usage(&self, user_id: AddressId, sc_address: &ManagedAddress, amount: &BigUint) {
+        self.total_deposit_usage(sc_address)
+            .update(|usage| self.deduct_usage(usage, amount));
+        self.user_deposit_usage(user_id, sc_address)
+            .update(|usage| self.deduct_usage(usage, amount));
+    }
+
+    /// The total usage is unchanged, but the receiver's cap is checked
+    fn transfer_positions_usage(
+        &self,
+        from_id: AddressId,
+        to_id: AddressId,
+        positions: &PaymentsVec<Self::Api>,
+    ) {
+        for position in positions.iter() {
+            if let Some((sc_address, amount)) = self.get_position_usage(&position) {
+                self.user_deposit_usage(from_id, &sc_address)
+                    .update(|usage| self.deduct_usage(usage, &amount));
+                self.add_user_usage(to_id, &sc_address, &amount, true);
+            }
+        }
+    }
+
+    /// Compounding is exempt from the caps: it is allowed while the usage is under the caps,
+    /// and the compounded amount is added in full, even if it takes the usage over them.
+    fn can_compound_into(&self, user_id: AddressId, sc_address: &ManagedAddress) -> bool {
+        let total_cap = self.total_deposit_cap(sc_address).get();
+        if total_cap > 0 && self.total_deposit_usage(sc_address).get() >= total_cap {
+            return false;
+        }
+
+        let user_cap = self.user_deposit_cap(sc_address).get();
+        user_cap == 0 || self.user_deposit_usage(user_id, sc_address).get() < user_cap
+    }
+
+    fn add_compounded_usage(
+        &self,
+        user_id: AddressId,
+        sc_address: &ManagedAddress,
+        amount: &BigUint,
+    ) {
+        self.add_total_usage(sc_address, amount, false);
+        self.add_user_usage(user_id, sc_address, amount, false);
+    }
+
+    fn add_total_usage(&self, sc_address: &ManagedAddress, amount: &BigUint, check_cap: bool) {
+        let usage_mapper = self.total_deposit_usage(sc_address);
+        let new_usage = usage_mapper.get() + amount;
+        if check_cap {
+            let cap = self.total_deposit_cap(sc_address).get();
+            require!(cap == 0 || new_usage <= cap, "Deposit cap exceeded");
+        }
+
+        usage_mapper.set(new_usage);
+    }
+
+    fn add_user_usage(
+        &self,
+        user_id: AddressId,
+        sc_address: &ManagedAddress,
+        amount: &BigUint,
+        check_cap: bool,
+    ) {
+        let usage_mapper = self.user_deposit_usage(user_id, sc_address);
+        let new_usage = usage_mapper.get() + amount;
+        if check_cap {
+            let cap = self.user_deposit_cap(sc_address).get();
+            require!(cap == 0 || new_usage <= cap, "User deposit cap exceeded");
+        }
+
+        usage_mapper.set(new_usage);
+    }
+
+    /// The usage of metastaking positions is recomputed from the current attributes,
+    /// so the deducted amount may differ from the added one by rounding.
+    fn deduct_usage(&self, usage: &mut BigUint, amount: &BigUint) {
+        if &*usage > amount {
+            *usage -= amount;
+        } else {
+            *usage = BigUint::zero();
+        }
+    }
+
+    /// Returns the farm or metastaking address of the position, and its amount of farming tokens.
+    /// Positions of contracts that are no longer whitelisted return `None`.
+    fn get_position_usage(&self, position: &EsdtTokenPayment) -> Option<(ManagedAddress, BigUint)> {
+        let farm_id = self.farm_for_farm_token(&position.token_identifier).get();
+        if farm_id != NULL_ID {
+            let farm_address = self.farm_ids().get_address(farm_id)?;
+            return Some((farm_address, position.amount.clone()));
+        }
+
+        let ms_id = self
+            .metastaking_for_dual_yield_token(&position.token_identifier)
+            .get();
+        let ms_address = self.metastaking_ids().get_address(ms_id)?;
+        let attributes: DualYieldTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_token_attributes(&position.token_identifier, position.token_nonce);
+        if attributes.virtual_pos_token_amount == 0 {
+            return Some((ms_address, BigUint::zero()));
+        }
+
+        let lp_amount = &attributes.lp_farm_token_amount * &position.amount
+            / &attributes.virtual_pos_token_amount;
+
+        Some((ms_address, lp_amount))
+    }
+
+    #[storage_mapper("totalDepositCap")]
+    fn total_deposit_cap(&self, sc_address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("userDepositCap")]
+    fn user_deposit_cap(&self, sc_address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[view(getMinDepositForAmount)]
+    #[storage_mapper("minDepositForAmount")]
+    fn min_deposit_for_amount(&self, sc_address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("totalDepositUsage")]
+    fn total_deposit_usage(&self, sc_address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("userDepositUsage")]
+    fn user_deposit_usage(
+        &self,
+        user_id: AddressId,
+        sc_address: &ManagedAddress,
+    ) -> SingleValueMapper<BigUint>;
+}
@@ -1,6 +1,12 @@
 use common_structs::PaymentsVec;
 
-use crate::common::{chain_info::CurrentChainInfo, rewards_wrapper::MergedRewardsWrapper};
+use crate::{
+    common::{chain_info::CurrentChainInfo, rewards_wrapper::MergedRewardsWrapper},
+    contract_state::ContractState,
+    fee_tiers::FeeTier,
+    inactive_farms::InactiveFarmPolicy,
+    user_tokens::user_strategy::CompoundStrategy,
+};
 
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
@@ -8,6 +14,7 @@ multiversx_sc::derive_imports!();
 #[type_abi]
 #[derive(TopEncode)]
 pub struct TokenDepositEvent<'a, M: ManagedTypeApi> {
+    pub payer: &'a ManagedAddress<M>,
     pub tokens: &'a PaymentsVec<M>,
     pub chain_info: CurrentChainInfo,
 }
@@ -17,6 +24,8 @@ pub struct TokenDepositEvent<'a, M: ManagedTypeApi> {
 pub enum DepositType {
     FarmTokens,
     MetastakingTokens,
+    LockedTokens,
+    WrappedFarmTokens,
 }
 
 #[type_abi]
@@ -33,13 +42,73 @@ pub enum WithdrawType {
     MetastakingTokens,
     RewardTokens,
     AllTokens,
+    LockedTokens,
+    WrappedFarmTokens,
+    AutoPayout,
 }
 
 #[type_abi]
 #[derive(TopEncode)]
 pub struct ProxyClaimEvent<'a, M: ManagedTypeApi> {
+    pub strategy: CompoundStrategy,
     pub available_rewards: &'a MergedRewardsWrapper<M>,
     pub updated_user_tokens: &'a PaymentsVec<M>,
+    pub energy_updated_contracts: &'a ManagedVec<M, ManagedAddress<M>>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct EnergyCompoundEvent<'a, M: ManagedTypeApi> {
+    pub new_locked_tokens: &'a EsdtTokenPayment<M>,
+    pub energy_added: &'a BigUint<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct BatchProcessedEvent {
+    pub processed_users: usize,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct PositionsTransferEvent<'a, M: ManagedTypeApi> {
+    pub positions: &'a PaymentsVec<M>,
+    pub rewards: &'a PaymentsVec<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct FarmMigrationEvent<'a, M: ManagedTypeApi> {
+    pub old_position: &'a EsdtTokenPayment<M>,
+    pub new_position: &'a EsdtTokenPayment<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct FarmMigrationFailedEvent<'a, M: ManagedTypeApi> {
+    pub old_position: &'a EsdtTokenPayment<M>,
+    pub farming_tokens: &'a EsdtTokenPayment<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct InactiveFarmPositionEvent<'a, M: ManagedTypeApi> {
+    pub position: &'a EsdtTokenPayment<M>,
+    pub policy: InactiveFarmPolicy,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[type_abi]
+#[derive(TopEncode)]
+pub struct KeeperCompensationEvent<'a, M: ManagedTypeApi> {
+    pub input_tokens: &'a EsdtTokenPayment<M>,
+    pub egld_amount: &'a BigUint<M>,
     pub chain_info: CurrentChainInfo,
 }
 
@@ -49,8 +118,10 @@ pub trait EventsModule {
         self.user_register_event(user, CurrentChainInfo::new::<Self::Api>())
     }
 
+    /// The payer is the address that sent the tokens, and the user is the account credited with them
     fn emit_token_deposit_event(
         &self,
+        payer: &ManagedAddress,
         user: &ManagedAddress,
         deposit_type: DepositType,
         tokens: &PaymentsVec<Self::Api>,
@@ -59,6 +130,7 @@ pub trait EventsModule {
             user,
             deposit_type,
             TokenDepositEvent {
+                payer,
                 tokens,
                 chain_info: CurrentChainInfo::new::<Self::Api>(),
             },
@@ -84,14 +156,152 @@ pub trait EventsModule {
     fn emit_proxy_claim_event(
         &self,
         user: &ManagedAddress,
+        strategy: CompoundStrategy,
         available_rewards: &MergedRewardsWrapper<Self::Api>,
         updated_user_tokens: &PaymentsVec<Self::Api>,
+        energy_updated_contracts: &ManagedVec<ManagedAddress>,
     ) {
         self.proxy_claim_event(
             user,
             ProxyClaimEvent {
+                strategy,
                 available_rewards,
                 updated_user_tokens,
+                energy_updated_contracts,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_energy_compound_event(
+        &self,
+        user: &ManagedAddress,
+        new_locked_tokens: &EsdtTokenPayment,
+        energy_added: &BigUint,
+    ) {
+        self.energy_compound_event(
+            user,
+            EnergyCompoundEvent {
+                new_locked_tokens,
+                energy_added,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_batch_processed_event(&self, keeper: &ManagedAddress, processed_users: usize) {
+        self.batch_processed_event(
+            keeper,
+            BatchProcessedEvent {
+                processed_users,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_keeper_compensation_event(
+        &self,
+        keeper: &ManagedAddress,
+        input_tokens: &EsdtTokenPayment,
+        egld_amount: &BigUint,
+    ) {
+        self.keeper_compensation_event(
+            keeper,
+            KeeperCompensationEvent {
+                input_tokens,
+                egld_amount,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_fee_percentage_change_event(&self, fee_percentage: u64) {
+        self.fee_percentage_change_event(fee_percentage, CurrentChainInfo::new::<Self::Api>());
+    }
+
+    fn emit_fee_tiers_change_event(&self, fee_tiers: &ManagedVec<FeeTier<Self::Api>>) {
+        self.fee_tiers_change_event(fee_tiers, CurrentChainInfo::new::<Self::Api>());
+    }
+
+    fn emit_user_fee_override_change_event(
+        &self,
+        user: &ManagedAddress,
+        opt_fee_percentage: Option<u64>,
+    ) {
+        self.user_fee_override_change_event(
+            user,
+            opt_fee_percentage,
+            CurrentChainInfo::new::<Self::Api>(),
+        );
+    }
+
+    fn emit_contract_state_change_event(&self, state: ContractState) {
+        self.contract_state_change_event(state, CurrentChainInfo::new::<Self::Api>());
+    }
+
+    fn emit_positions_transfer_event(
+        &self,
+        from: &ManagedAddress,
+        to: &ManagedAddress,
+        positions: &PaymentsVec<Self::Api>,
+        rewards: &PaymentsVec<Self::Api>,
+    ) {
+        self.positions_transfer_event(
+            from,
+            to,
+            PositionsTransferEvent {
+                positions,
+                rewards,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_farm_migration_event(
+        &self,
+        user: &ManagedAddress,
+        old_position: &EsdtTokenPayment,
+        new_position: &EsdtTokenPayment,
+    ) {
+        self.farm_migration_event(
+            user,
+            FarmMigrationEvent {
+                old_position,
+                new_position,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_farm_migration_failed_event(
+        &self,
+        user: &ManagedAddress,
+        old_position: &EsdtTokenPayment,
+        farming_tokens: &EsdtTokenPayment,
+    ) {
+        self.farm_migration_failed_event(
+            user,
+            FarmMigrationFailedEvent {
+                old_position,
+                farming_tokens,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_inactive_farm_position_event(
+        &self,
+        user: &ManagedAddress,
+        farm_address: &ManagedAddress,
+        position: &EsdtTokenPayment,
+        policy: InactiveFarmPolicy,
+    ) {
+        self.inactive_farm_position_event(
+            user,
+            farm_address,
+            InactiveFarmPositionEvent {
+                position,
+                policy,
                 chain_info: CurrentChainInfo::new::<Self::Api>(),
             },
         );
@@ -126,4 +336,84 @@ pub trait EventsModule {
         #[indexed] user: &ManagedAddress,
         event_data: ProxyClaimEvent<Self::Api>,
     );
+
+    #[event("energyCompound")]
+    fn energy_compound_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        event_data: EnergyCompoundEvent<Self::Api>,
+    );
+
+    #[event("batchProcessed")]
+    fn batch_processed_event(
+        &self,
+        #[indexed] keeper: &ManagedAddress,
+        event_data: BatchProcessedEvent,
+    );
+
+    #[event("keeperCompensation")]
+    fn keeper_compensation_event(
+        &self,
+        #[indexed] keeper: &ManagedAddress,
+        event_data: KeeperCompensationEvent<Self::Api>,
+    );
+
+    #[event("feePercentageChange")]
+    fn fee_percentage_change_event(
+        &self,
+        #[indexed] fee_percentage: u64,
+        current_chain_info: CurrentChainInfo,
+    );
+
+    #[event("feeTiersChange")]
+    fn fee_tiers_change_event(
+        &self,
+        fee_tiers: &ManagedVec<FeeTier<Self::Api>>,
+        current_chain_info: CurrentChainInfo,
+    );
+
+    #[event("userFeeOverrideChange")]
+    fn user_fee_override_change_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] opt_fee_percentage: Option<u64>,
+        current_chain_info: CurrentChainInfo,
+    );
+
+    #[event("contractStateChange")]
+    fn contract_state_change_event(
+        &self,
+        #[indexed] state: ContractState,
+        current_chain_info: CurrentChainInfo,
+    );
+
+    #[event("positionsTransfer")]
+    fn positions_transfer_event(
+        &self,
+        #[indexed] from: &ManagedAddress,
+        #[indexed] to: &ManagedAddress,
+        event_data: PositionsTransferEvent<Self::Api>,
+    );
+
+    #[event("farmMigration")]
+    fn farm_migration_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        event_data: FarmMigrationEvent<Self::Api>,
+    );
+
+    #[event("farmMigrationFailed")]
+    fn farm_migration_failed_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        event_data: FarmMigrationFailedEvent<Self::Api>,
+    );
+
+    #[event("inactiveFarmPosition")]
+    fn inactive_farm_position_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] farm_address: &ManagedAddress,
+        event_data: InactiveFarmPositionEvent<Self::Api>,
+    );
 }
@@ -0,0 +1,153 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::Epoch;
+use energy_factory::lock_options::LockOption;
+use energy_factory::ProxyTrait as _;
+use multiversx_sc::storage::StorageKey;
+
+pub static LOCK_OPTIONS_STORAGE_KEY: &[u8] = b"lockOptions";
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, PartialEq, Debug)]
+pub struct EnergyCompoundResult<M: ManagedTypeApi> {
+    pub epoch: Epoch,
+    pub energy_added: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait EnergyCompoundingModule:
+    crate::common::common_storage::CommonStorageModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::user_locked_tokens::UserLockedTokensModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// lock_epochs: The lock period the locked token rewards are extended to on every claim.
+    ///     Must be one of the energy factory's lock options. 0 disables energy compounding.
+    ///
+    /// The extended tokens are sent directly to the user. The energy factory cannot extend
+    /// a position held by auto-farm, so energy compounding is not available
+    /// while the user has deposited locked tokens.
+    #[endpoint(setEnergyCompoundLockEpochs)]
+    fn set_energy_compound_lock_epochs(&self, lock_epochs: Epoch) {
+        self.require_not_withdraw_only();
+
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        if lock_epochs != 0 {
+            self.require_valid_lock_epochs(lock_epochs);
+            require!(
+                self.user_locked_tokens(user_id).is_empty(),
+                "Cannot compound energy with deposited locked tokens"
+            );
+        }
+
+        self.user_energy_compound_lock_epochs(user_id)
+            .set(lock_epochs);
+    }
+
+    #[view(getUserEnergyCompoundLockEpochs)]
+    fn get_user_energy_compound_lock_epochs_view(&self, user: ManagedAddress) -> Epoch {
+        let user_id = self.user_ids().get_id(&user);
+        self.user_energy_compound_lock_epochs(user_id).get()
+    }
+
+    #[view(getLastEnergyCompound)]
+    fn get_last_energy_compound_view(
+        &self,
+        user: ManagedAddress,
+    ) -> OptionalValue<EnergyCompoundResult<Self::Api>> {
+        let user_id = self.user_ids().get_id(&user);
+        let mapper = self.last_energy_compound(user_id);
+        if user_id != NULL_ID && !mapper.is_empty() {
+            OptionalValue::Some(mapper.get())
+        } else {
+            OptionalValue::None
+        }
+    }
+
+    fn compound_locked_rewards_into_energy(&self, user: &ManagedAddress, user_id: AddressId) {
+        let lock_epochs = self.user_energy_compound_lock_epochs(user_id).get();
+        if lock_epochs == 0 {
+            return;
+        }
+
+        let user_rewards_mapper = self.user_rewards(user_id);
+        if user_rewards_mapper.is_empty() {
+            return;
+        }
+
+        let mut user_wrapped_rewards = user_rewards_mapper.get();
+        let opt_locked_tokens = user_wrapped_rewards.opt_locked_tokens.take();
+        if opt_locked_tokens.is_none() {
+            return;
+        }
+
+        let locked_tokens = unsafe { opt_locked_tokens.unwrap_unchecked() };
+        let energy_before = self.get_energy_amount(user);
+        let new_locked_tokens =
+            self.call_extend_lock_period(user.clone(), locked_tokens, lock_epochs);
+        let energy_after = self.get_energy_amount(user);
+        let energy_added = if energy_after > energy_before {
+            energy_after - energy_before
+        } else {
+            BigUint::zero()
+        };
+
+        user_rewards_mapper.set(&user_wrapped_rewards);
+        self.last_energy_compound(user_id)
+            .set(EnergyCompoundResult {
+                epoch: self.blockchain().get_block_epoch(),
+                energy_added: energy_added.clone(),
+            });
+
+        self.emit_energy_compound_event(user, &new_locked_tokens, &energy_added);
+    }
+
+    fn call_extend_lock_period(
+        &self,
+        user: ManagedAddress,
+        locked_tokens: EsdtTokenPayment,
+        lock_epochs: Epoch,
+    ) -> EsdtTokenPayment {
+        let energy_factory_address = self.energy_factory_address().get();
+        self.energy_factory_proxy(energy_factory_address)
+            .lock_tokens_endpoint(lock_epochs, OptionalValue::Some(user))
+            .with_esdt_transfer(locked_tokens)
+            .execute_on_dest_context()
+    }
+
+    fn require_valid_lock_epochs(&self, lock_epochs: Epoch) {
+        let energy_factory_address = self.energy_factory_address().get();
+        let lock_options = self.get_lock_options_mapper(energy_factory_address).get();
+        for lock_option in lock_options.iter() {
+            if lock_option.lock_epochs == lock_epochs {
+                return;
+            }
+        }
+
+        sc_panic!("Invalid lock epochs");
+    }
+
+    fn get_lock_options_mapper(
+        &self,
+        energy_factory_address: ManagedAddress,
+    ) -> SingleValueMapper<ManagedVec<LockOption>, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            energy_factory_address,
+            StorageKey::new(LOCK_OPTIONS_STORAGE_KEY),
+        )
+    }
+
+    #[storage_mapper("lastEnergyCompound")]
+    fn last_energy_compound(
+        &self,
+        user_id: AddressId,
+    ) -> SingleValueMapper<EnergyCompoundResult<Self::Api>>;
+}
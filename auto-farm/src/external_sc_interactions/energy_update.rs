@@ -0,0 +1,136 @@
+multiversx_sc::imports!();
+
+use common_structs::Epoch;
+use read_external_storage::State;
+
+#[multiversx_sc::module]
+pub trait EnergyUpdateModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Updates the user's energy in the farms and metastaking contracts where the user
+    /// holds positions, and in the fees collector.
+    ///
+    /// Contracts which already received the user's energy in the current epoch are skipped.
+    ///
+    /// Returns the addresses of the contracts that were updated
+    fn update_energy_all_contracts(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+    ) -> ManagedVec<ManagedAddress> {
+        let mut updated_contracts = ManagedVec::new();
+        let user_energy = self.get_energy_amount(user);
+
+        let farms_mapper = self.farm_ids();
+        for farm_id in self.user_farm_ids(user_id).iter() {
+            if let Some(farm_addr) = farms_mapper.get_address(farm_id) {
+                self.update_energy_single_farm(
+                    user,
+                    user_id,
+                    &user_energy,
+                    farm_addr,
+                    &mut updated_contracts,
+                );
+            }
+        }
+
+        let ms_mapper = self.metastaking_ids();
+        for ms_id in self.user_metastaking_ids(user_id).iter() {
+            if let Some(ms_addr) = ms_mapper.get_address(ms_id) {
+                let lp_farm_addr = self.get_lp_farm_address_mapper(ms_addr.clone()).get();
+                let staking_farm_addr = self.get_staking_farm_address_mapper(ms_addr).get();
+                self.update_energy_single_farm(
+                    user,
+                    user_id,
+                    &user_energy,
+                    lp_farm_addr,
+                    &mut updated_contracts,
+                );
+                self.update_energy_single_farm(
+                    user,
+                    user_id,
+                    &user_energy,
+                    staking_farm_addr,
+                    &mut updated_contracts,
+                );
+            }
+        }
+
+        let fees_collector_addr = self.fees_collector_sc_address().get();
+        if self.should_update_energy(user_id, &fees_collector_addr, &user_energy) {
+            self.fees_collector_proxy(fees_collector_addr.clone())
+                .update_energy_for_user(user.clone())
+                .execute_on_dest_context::<()>();
+            updated_contracts.push(fees_collector_addr);
+        }
+
+        updated_contracts
+    }
+
+    fn update_energy_single_farm(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        user_energy: &BigUint,
+        farm_addr: ManagedAddress,
+        updated_contracts: &mut ManagedVec<ManagedAddress>,
+    ) {
+        if self.get_farm_state(farm_addr.clone()) != State::Active {
+            return;
+        }
+        if !self.should_update_energy(user_id, &farm_addr, user_energy) {
+            return;
+        }
+
+        self.farm_proxy(farm_addr.clone())
+            .update_energy_for_user(user.clone())
+            .execute_on_dest_context::<()>();
+        updated_contracts.push(farm_addr);
+    }
+
+    /// Returns `true` if the contract did not yet receive the user's energy in the current epoch.
+    /// The epoch is part of the check, as the energy decays even if the amount looks unchanged.
+    /// In that case, the epoch and energy are also saved as the last ones sent to that contract.
+    fn should_update_energy(
+        &self,
+        user_id: AddressId,
+        sc_address: &ManagedAddress,
+        user_energy: &BigUint,
+    ) -> bool {
+        let current_epoch = self.blockchain().get_block_epoch();
+        let mut last_energy_mapper = self.user_last_updated_energy(user_id);
+        if let Some((last_epoch, last_energy)) = last_energy_mapper.get(sc_address) {
+            if last_epoch == current_epoch && &last_energy == user_energy {
+                return false;
+            }
+        }
+
+        let _ = last_energy_mapper.insert(sc_address.clone(), (current_epoch, user_energy.clone()));
+
+        true
+    }
+
+    #[storage_mapper("userLastUpdatedEnergy")]
+    fn user_last_updated_energy(
+        &self,
+        user_id: AddressId,
+    ) -> MapMapper<ManagedAddress, (Epoch, BigUint)>;
+}
@@ -2,13 +2,13 @@ multiversx_sc::imports!();
 
 use common_structs::PaymentsVec;
 use farm::{
-    base_functions::{ClaimRewardsResultType, ClaimRewardsResultWrapper},
-    EnterFarmResultType,
+    base_functions::{ClaimRewardsResultType, ClaimRewardsResultWrapper, ExitFarmResultWrapper},
+    EnterFarmResultType, ExitFarmWithPartialPosResultType,
 };
 use farm_staking::stake_farm::ProxyTrait as _;
 use read_external_storage::State;
 
-use crate::common::rewards_wrapper::RewardsWrapper;
+use crate::{common::rewards_wrapper::RewardsWrapper, inactive_farms::InactiveFarmPolicy};
 
 #[multiversx_sc::module]
 pub trait FarmActionsModule:
@@ -18,6 +18,7 @@ pub trait FarmActionsModule:
     + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
     + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
     + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::inactive_farms::InactiveFarmsModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
     + crate::fees::FeesModule
     + crate::events::EventsModule
@@ -27,6 +28,9 @@ pub trait FarmActionsModule:
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
 {
+    /// Positions of the same farm are merged into a single position while claiming.
+    /// Staking farms only accept one position per claim, so their positions are claimed one by one.
+    /// Positions of inactive farms are handled according to the farm's inactive policy.
     fn claim_all_farm_rewards(
         &self,
         user: &ManagedAddress,
@@ -34,54 +38,101 @@ pub trait FarmActionsModule:
         rew_wrapper: &mut RewardsWrapper<Self::Api>,
     ) {
         let farms_mapper = self.farm_ids();
-        let user_tokens_mapper = self.user_farm_tokens(user_id);
-        let user_farm_tokens = user_tokens_mapper.get();
-        if user_farm_tokens.is_empty() {
-            return;
-        }
-
-        let mut new_user_farm_tokens = PaymentsVec::new();
-        for farm_token in user_farm_tokens.iter() {
-            let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
+        let user_farm_ids: ManagedVec<Self::Api, AddressId> =
+            self.user_farm_ids(user_id).iter().collect();
+        let mut exited_farming_tokens = PaymentsVec::new();
+        for farm_id in user_farm_ids.iter() {
             let opt_farm_addr = farms_mapper.get_address(farm_id);
             if opt_farm_addr.is_none() {
-                new_user_farm_tokens.push(farm_token.clone());
                 continue;
             }
 
             let farm_addr = unsafe { opt_farm_addr.unwrap_unchecked() };
+            let farm_tokens = self.user_farm_positions(user_id, farm_id).get();
             let farm_state = self.get_farm_state(farm_addr.clone());
             if farm_state != State::Active {
-                new_user_farm_tokens.push(farm_token.clone());
+                let policy = self.inactive_farm_policy(farm_id).get();
+                match policy {
+                    InactiveFarmPolicy::Skip => {}
+                    InactiveFarmPolicy::Notify => {
+                        for farm_token in farm_tokens.iter() {
+                            self.emit_inactive_farm_position_event(
+                                user,
+                                &farm_addr,
+                                &farm_token,
+                                policy,
+                            );
+                        }
+                    }
+                    InactiveFarmPolicy::Exit => {
+                        self.remove_positions_usage(user_id, &farm_tokens);
+                        for farm_token in farm_tokens.iter() {
+                            let exit_result = self.call_exit_farm(
+                                farm_addr.clone(),
+                                user.clone(),
+                                farm_token.clone(),
+                            );
+                            if exit_result.rewards.amount > 0 {
+                                rew_wrapper.add_tokens(exit_result.rewards);
+                            }
+                            exited_farming_tokens.push(exit_result.farming_tokens);
+                            self.emit_inactive_farm_position_event(
+                                user,
+                                &farm_addr,
+                                &farm_token,
+                                policy,
+                            );
+                        }
+                        self.set_user_farm_positions(user_id, farm_id, &PaymentsVec::new());
+                    }
+                }
+
+                continue;
+            }
+
+            let is_staking_farm = self
+                .get_farm_pair_contract_address_mapper(farm_addr.clone())
+                .is_empty();
+            if !is_staking_farm {
+                let claim_result = self.call_farm_claim(farm_addr, user.clone(), farm_tokens);
+                self.user_farm_positions(user_id, farm_id)
+                    .set(PaymentsVec::from_single_item(claim_result.new_farm_token));
+
+                rew_wrapper.add_tokens(claim_result.rewards);
                 continue;
             }
 
-            let claim_result = self.call_farm_claim(farm_addr, user.clone(), farm_token.clone());
-            new_user_farm_tokens.push(claim_result.new_farm_token);
+            let mut new_farm_tokens = PaymentsVec::new();
+            for farm_token in farm_tokens.iter() {
+                let claim_result = self.call_farm_claim(
+                    farm_addr.clone(),
+                    user.clone(),
+                    PaymentsVec::from_single_item(farm_token.clone()),
+                );
+                new_farm_tokens.push(claim_result.new_farm_token);
 
-            rew_wrapper.add_tokens(claim_result.rewards);
+                rew_wrapper.add_tokens(claim_result.rewards);
+            }
+            self.user_farm_positions(user_id, farm_id)
+                .set(&new_farm_tokens);
         }
 
-        user_tokens_mapper.set(&new_user_farm_tokens);
+        if !exited_farming_tokens.is_empty() {
+            self.return_tokens_to_user_rewards(user_id, exited_farming_tokens);
+        }
     }
 
-    /// user_farm_ids contains the associated farm_id for each token in user_farm_tokens
+    /// The new tokens are added to the user's position in the farm with the same farming token
     fn compound_staking_rewards_with_existing_farm_position(
         &self,
         user: &ManagedAddress,
-        user_farm_tokens: &mut PaymentsVec<Self::Api>,
-        user_farm_ids: &ManagedVec<AddressId>,
+        user_id: AddressId,
         new_tokens: EsdtTokenPayment,
     ) -> Result<(), ()> {
         let farm_id = self
             .farm_for_farming_token(&new_tokens.token_identifier)
             .get();
-        if farm_id == NULL_ID {
-            return Result::Err(());
-        }
-
-        let opt_existing_farm_index = user_farm_ids.find(&farm_id);
-        if opt_existing_farm_index.is_none() {
+        if farm_id == NULL_ID || !self.user_farm_ids(user_id).contains(&farm_id) {
             return Result::Err(());
         }
 
@@ -90,16 +141,23 @@ pub trait FarmActionsModule:
             return Result::Err(());
         }
 
-        let existing_farm_index = unsafe { opt_existing_farm_index.unwrap_unchecked() };
         let farm_addr = unsafe { opt_farm_addr.unwrap_unchecked() };
-        let existing_farm_pos = user_farm_tokens.get(existing_farm_index).clone();
+        if !self.can_compound_into(user_id, &farm_addr) {
+            return Result::Err(());
+        }
+
+        self.add_compounded_usage(user_id, &farm_addr, &new_tokens.amount);
+        let positions_mapper = self.user_farm_positions(user_id, farm_id);
+        let mut positions = positions_mapper.get();
+        let existing_farm_pos = positions.get(0).clone();
         let new_farm_token = self.call_enter_farm_staking_with_additional_tokens(
             farm_addr,
             user.clone(),
             existing_farm_pos,
             new_tokens,
         );
-        let _ = user_farm_tokens.set(existing_farm_index, new_farm_token);
+        let _ = positions.set(0, new_farm_token);
+        positions_mapper.set(&positions);
 
         Result::Ok(())
     }
@@ -123,16 +181,35 @@ pub trait FarmActionsModule:
         new_farm_token
     }
 
-    fn call_farm_claim(
+    fn call_enter_farm_with_additional_tokens(
         &self,
         farm_addr: ManagedAddress,
         user: ManagedAddress,
         farm_token: EsdtTokenPayment,
+        new_tokens: EsdtTokenPayment,
+    ) -> EsdtTokenPayment {
+        let raw_results: EnterFarmResultType<Self::Api> = self
+            .farm_proxy(farm_addr)
+            .enter_farm_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(new_tokens)
+            .with_esdt_transfer(farm_token)
+            .execute_on_dest_context();
+
+        // since we already claimed, there are no boosted rewards
+        let (new_farm_token, _) = raw_results.into_tuple();
+        new_farm_token
+    }
+
+    fn call_farm_claim(
+        &self,
+        farm_addr: ManagedAddress,
+        user: ManagedAddress,
+        farm_tokens: PaymentsVec<Self::Api>,
     ) -> ClaimRewardsResultWrapper<Self::Api> {
         let raw_results: ClaimRewardsResultType<Self::Api> = self
             .farm_proxy(farm_addr)
             .claim_rewards_endpoint(OptionalValue::Some(user))
-            .with_esdt_transfer(farm_token)
+            .with_multi_token_transfer(farm_tokens)
             .execute_on_dest_context();
         let (new_farm_token, rewards) = raw_results.into_tuple();
 
@@ -142,6 +219,25 @@ pub trait FarmActionsModule:
         }
     }
 
+    fn call_exit_farm(
+        &self,
+        farm_addr: ManagedAddress,
+        user: ManagedAddress,
+        farm_token: EsdtTokenPayment,
+    ) -> ExitFarmResultWrapper<Self::Api> {
+        let raw_results: ExitFarmWithPartialPosResultType<Self::Api> = self
+            .farm_proxy(farm_addr)
+            .exit_farm_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(farm_token)
+            .execute_on_dest_context();
+        let (farming_tokens, rewards) = raw_results.into_tuple();
+
+        ExitFarmResultWrapper {
+            farming_tokens,
+            rewards,
+        }
+    }
+
     #[proxy]
     fn farm_proxy(&self, sc_address: ManagedAddress) -> farm_with_locked_rewards::Proxy<Self::Api>;
 
@@ -0,0 +1,269 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+use farm::EnterFarmResultType;
+
+use crate::common::{common_storage::MAX_PERCENTAGE, rewards_wrapper::RewardsWrapper};
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, PartialEq, Debug)]
+pub struct FarmMigration<M: ManagedTypeApi> {
+    pub old_farm_address: ManagedAddress<M>,
+    pub new_farm_address: ManagedAddress<M>,
+    pub min_output_percentage: u64,
+}
+
+#[multiversx_sc::module]
+pub trait FarmMigrationModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + utils::UtilsModule
+    + energy_query::EnergyQueryModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+{
+    /// Positions of the old farm are moved to the new farm while processing users.
+    /// The old farm must already be removed from the whitelist, and the new farm must be whitelisted.
+    ///
+    /// min_output_percentage is the minimum amount of farming tokens received when exiting
+    /// the old farm, as a percentage of the position, where 10_000 is 100%.
+    #[only_owner]
+    #[endpoint(addFarmMigration)]
+    fn add_farm_migration(
+        &self,
+        old_farm_address: ManagedAddress,
+        new_farm_address: ManagedAddress,
+        min_output_percentage: u64,
+    ) {
+        require!(
+            min_output_percentage <= MAX_PERCENTAGE,
+            "Invalid min output percentage"
+        );
+
+        let farms_mapper = self.farm_ids();
+        require!(
+            farms_mapper.get_id(&old_farm_address) == NULL_ID,
+            "Old farm must be removed first"
+        );
+        require!(
+            farms_mapper.get_id(&new_farm_address) != NULL_ID,
+            "New farm not whitelisted"
+        );
+
+        let old_farm_config = self.get_farm_config(old_farm_address.clone());
+        let new_farm_config = self.get_farm_config(new_farm_address.clone());
+        require!(
+            old_farm_config.farming_token_id == new_farm_config.farming_token_id,
+            "Farms have different farming tokens"
+        );
+
+        self.farm_migration(&old_farm_config.farm_token_id)
+            .set(FarmMigration {
+                old_farm_address,
+                new_farm_address,
+                min_output_percentage,
+            });
+    }
+
+    #[only_owner]
+    #[endpoint(removeFarmMigration)]
+    fn remove_farm_migration(&self, old_farm_token_id: TokenIdentifier) {
+        let migration_mapper = self.farm_migration(&old_farm_token_id);
+        require!(!migration_mapper.is_empty(), "Unknown farm migration");
+
+        migration_mapper.clear();
+    }
+
+    /// Exits the user's positions from replaced farms, and enters the new farms with the farming tokens.
+    /// Positions of farms without a migration, or whose new farm is no longer whitelisted, are kept as they are.
+    /// A position whose exit output is below the migration's minimum does not revert the batch.
+    /// Its farming tokens are returned to the user's rewards, and a farmMigrationFailed event is emitted.
+    fn migrate_farm_positions(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        rew_wrapper: &mut RewardsWrapper<Self::Api>,
+    ) {
+        let user_farm_ids: ManagedVec<Self::Api, AddressId> =
+            self.user_farm_ids(user_id).iter().collect();
+        for farm_id in user_farm_ids.iter() {
+            let positions = self.user_farm_positions(user_id, farm_id).get();
+            let mut remaining_positions = PaymentsVec::new();
+            let mut migrated_any = false;
+            for farm_token in positions.iter() {
+                let migration_mapper = self.farm_migration(&farm_token.token_identifier);
+                if migration_mapper.is_empty() {
+                    remaining_positions.push(farm_token.clone());
+                    continue;
+                }
+
+                let migration = migration_mapper.get();
+                let new_farm_id = self.farm_ids().get_id(&migration.new_farm_address);
+                if new_farm_id == NULL_ID {
+                    remaining_positions.push(farm_token.clone());
+                    continue;
+                }
+
+                // the old farm is no longer whitelisted, so its usage is removed by address
+                self.remove_usage(user_id, &migration.old_farm_address, &farm_token.amount);
+                migrated_any = true;
+                let opt_new_farm_token = self.migrate_single_position(
+                    user,
+                    user_id,
+                    &migration,
+                    &farm_token,
+                    rew_wrapper,
+                );
+                if opt_new_farm_token.is_none() {
+                    continue;
+                }
+
+                let new_farm_token = unsafe { opt_new_farm_token.unwrap_unchecked() };
+                self.update_migration_progress(user_id, &farm_token);
+                self.add_compounded_usage(
+                    user_id,
+                    &migration.new_farm_address,
+                    &new_farm_token.amount,
+                );
+                self.emit_farm_migration_event(user, &farm_token, &new_farm_token);
+
+                self.add_user_farm_position(user_id, new_farm_id, new_farm_token);
+            }
+
+            if migrated_any {
+                self.set_user_farm_positions(user_id, farm_id, &remaining_positions);
+            }
+        }
+    }
+
+    /// If the farming tokens received from the old farm are below the migration's minimum,
+    /// they are returned to the user's rewards instead of entering the new farm.
+    ///
+    /// Returns the new farm token, if the position was migrated
+    fn migrate_single_position(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        migration: &FarmMigration<Self::Api>,
+        farm_token: &EsdtTokenPayment,
+        rew_wrapper: &mut RewardsWrapper<Self::Api>,
+    ) -> Option<EsdtTokenPayment> {
+        let exit_result = self.call_exit_farm(
+            migration.old_farm_address.clone(),
+            user.clone(),
+            farm_token.clone(),
+        );
+        if exit_result.rewards.amount > 0 {
+            rew_wrapper.add_tokens(exit_result.rewards);
+        }
+
+        let farming_tokens = exit_result.farming_tokens;
+        let min_farming_amount =
+            &farm_token.amount * migration.min_output_percentage / MAX_PERCENTAGE;
+        if farming_tokens.amount < min_farming_amount {
+            self.emit_farm_migration_failed_event(user, farm_token, &farming_tokens);
+            self.return_tokens_to_user_rewards(
+                user_id,
+                PaymentsVec::from_single_item(farming_tokens),
+            );
+
+            return None;
+        }
+
+        let raw_results: EnterFarmResultType<Self::Api> = self
+            .farm_proxy(migration.new_farm_address.clone())
+            .enter_farm_endpoint(OptionalValue::Some(user.clone()))
+            .with_esdt_transfer(farming_tokens)
+            .execute_on_dest_context();
+        let (new_farm_token, boosted_rewards) = raw_results.into_tuple();
+        if boosted_rewards.amount > 0 {
+            rew_wrapper.add_tokens(boosted_rewards);
+        }
+
+        Some(new_farm_token)
+    }
+
+    fn update_migration_progress(&self, user_id: AddressId, old_farm_token: &EsdtTokenPayment) {
+        let old_farm_token_id = &old_farm_token.token_identifier;
+        let is_new_user = self
+            .user_migrated_farm_tokens(user_id)
+            .insert(old_farm_token_id.clone());
+        if is_new_user {
+            self.migrated_users_count(old_farm_token_id)
+                .update(|count| *count += 1);
+        }
+
+        self.migrated_amount(old_farm_token_id)
+            .update(|amount| *amount += &old_farm_token.amount);
+    }
+
+    #[view(getFarmMigration)]
+    fn get_farm_migration_view(
+        &self,
+        old_farm_token_id: TokenIdentifier,
+    ) -> OptionalValue<FarmMigration<Self::Api>> {
+        let migration_mapper = self.farm_migration(&old_farm_token_id);
+        if migration_mapper.is_empty() {
+            return OptionalValue::None;
+        }
+
+        OptionalValue::Some(migration_mapper.get())
+    }
+
+    /// Returns the number of migrated users and the total migrated amount of old farm tokens
+    #[view(getFarmMigrationProgress)]
+    fn get_farm_migration_progress(
+        &self,
+        old_farm_token_id: TokenIdentifier,
+    ) -> MultiValue2<usize, BigUint> {
+        let users_count = self.migrated_users_count(&old_farm_token_id).get();
+        let amount = self.migrated_amount(&old_farm_token_id).get();
+
+        (users_count, amount).into()
+    }
+
+    /// Returns the old farm tokens for which the user had positions migrated
+    #[view(getUserMigratedFarmTokens)]
+    fn get_user_migrated_farm_tokens(
+        &self,
+        user: ManagedAddress,
+    ) -> MultiValueEncoded<TokenIdentifier> {
+        let mut result = MultiValueEncoded::new();
+        let user_id = self.user_ids().get_id(&user);
+        if user_id == NULL_ID {
+            return result;
+        }
+
+        for token_id in self.user_migrated_farm_tokens(user_id).iter() {
+            result.push(token_id);
+        }
+
+        result
+    }
+
+    #[storage_mapper("farmMigration")]
+    fn farm_migration(
+        &self,
+        old_farm_token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<FarmMigration<Self::Api>>;
+
+    #[storage_mapper("migratedUsersCount")]
+    fn migrated_users_count(&self, old_farm_token_id: &TokenIdentifier)
+        -> SingleValueMapper<usize>;
+
+    #[storage_mapper("migratedAmount")]
+    fn migrated_amount(&self, old_farm_token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("userMigratedFarmTokens")]
+    fn user_migrated_farm_tokens(&self, user_id: AddressId) -> UnorderedSetMapper<TokenIdentifier>;
+}
@@ -0,0 +1,242 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::common::{common_storage::MAX_PERCENTAGE, unique_payments::UniquePayments};
+
+pub const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
+
+#[multiversx_sc::module]
+pub trait KeeperCompensationModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + auto_pos_creator::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + auto_pos_creator::external_sc_interactions::router_actions::RouterActionsModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// share: Percentage of the treasury's non-locked fees converted to EGLD for the keepers,
+    ///     where 10_000 is 100%. A value of 0 disables the conversion.
+    /// max_slippage: Maximum accepted deviation of each conversion swap from the safe price.
+    ///
+    /// Fees are swapped to WEGLD through the router, then unwrapped through the EGLD wrapper,
+    /// so the addresses set through setPositionCreationAddresses are used.
+    #[only_owner]
+    #[endpoint(setKeeperCompensationConfig)]
+    fn set_keeper_compensation_config(
+        &self,
+        share: u64,
+        wegld_token_id: TokenIdentifier,
+        max_slippage: u64,
+    ) {
+        require!(share <= MAX_PERCENTAGE, "Invalid compensation share");
+        require!(max_slippage < MAX_PERCENTAGE, "Invalid slippage");
+        require!(
+            wegld_token_id.is_valid_esdt_identifier(),
+            "Invalid token ID"
+        );
+        require!(
+            !self.router_address().is_empty() && !self.egld_wrapper_address().is_empty(),
+            "Router and EGLD wrapper addresses not set"
+        );
+
+        self.keeper_compensation_share().set(share);
+        self.keeper_compensation_wegld_token_id()
+            .set(wegld_token_id);
+        self.keeper_compensation_max_slippage().set(max_slippage);
+    }
+
+    /// Only fee tokens with a pair are converted. The pair must be between the token and WEGLD,
+    /// so the compensation config must be set first.
+    #[only_owner]
+    #[endpoint(setKeeperCompensationPair)]
+    fn set_keeper_compensation_pair(
+        &self,
+        token_id: TokenIdentifier,
+        pair_address: ManagedAddress,
+    ) {
+        require!(
+            token_id != self.get_locked_token_id(),
+            "Locked tokens cannot be converted"
+        );
+        self.require_sc_address(&pair_address);
+
+        let wegld_mapper = self.keeper_compensation_wegld_token_id();
+        require!(!wegld_mapper.is_empty(), "Compensation config not set");
+        require!(
+            self.is_compensation_pair(&pair_address, &token_id, &wegld_mapper.get()),
+            "Invalid compensation pair"
+        );
+
+        self.keeper_compensation_pair(&token_id).set(pair_address);
+    }
+
+    /// Pending fees of the token are returned to the treasury at the end of the next keeper batch
+    #[only_owner]
+    #[endpoint(removeKeeperCompensationPair)]
+    fn remove_keeper_compensation_pair(&self, token_id: TokenIdentifier) {
+        let pair_mapper = self.keeper_compensation_pair(&token_id);
+        require!(!pair_mapper.is_empty(), "Unknown compensation pair");
+
+        pair_mapper.clear();
+    }
+
+    #[view(getKeeperCompensationPair)]
+    fn get_keeper_compensation_pair_view(
+        &self,
+        token_id: TokenIdentifier,
+    ) -> OptionalValue<ManagedAddress> {
+        let pair_mapper = self.keeper_compensation_pair(&token_id);
+        if pair_mapper.is_empty() {
+            return OptionalValue::None;
+        }
+
+        OptionalValue::Some(pair_mapper.get())
+    }
+
+    /// Converts the fees taken by the keeper's batches to EGLD and sends it to the keeper.
+    /// The swap minimum is derived from the pair's safe price.
+    /// Tokens whose swap would exceed the max slippage, or whose pair no longer matches,
+    /// are kept for the keeper's next batch.
+    fn convert_keeper_compensation(&self, keeper: &ManagedAddress) {
+        let pending_mapper = self.pending_keeper_compensation(keeper);
+        let wegld_mapper = self.keeper_compensation_wegld_token_id();
+        if pending_mapper.is_empty() || wegld_mapper.is_empty() {
+            return;
+        }
+
+        let wegld_token_id = wegld_mapper.get();
+        let max_slippage = self.keeper_compensation_max_slippage().get();
+        let pending_tokens = pending_mapper.take().into_payments();
+        let mut remaining_tokens = PaymentsVec::new();
+        let mut treasury_tokens = PaymentsVec::new();
+        let mut total_wegld = BigUint::zero();
+        for fee_tokens in pending_tokens.iter() {
+            let pair_mapper = self.keeper_compensation_pair(&fee_tokens.token_identifier);
+            if pair_mapper.is_empty() {
+                treasury_tokens.push(fee_tokens.clone());
+                continue;
+            }
+
+            let pair_address = pair_mapper.get();
+            if !self.is_compensation_pair(
+                &pair_address,
+                &fee_tokens.token_identifier,
+                &wegld_token_id,
+            ) {
+                remaining_tokens.push(fee_tokens.clone());
+                continue;
+            }
+
+            let opt_min_amount_out = self.get_safe_swap_min_amount_out(
+                &pair_address,
+                &fee_tokens.token_identifier,
+                &wegld_token_id,
+                &fee_tokens.amount,
+                max_slippage,
+            );
+            if opt_min_amount_out.is_none() {
+                remaining_tokens.push(fee_tokens.clone());
+                continue;
+            }
+
+            let min_amount_out = unsafe { opt_min_amount_out.unwrap_unchecked() };
+            let mut swap_operations = MultiValueEncoded::new();
+            swap_operations.push(
+                (
+                    pair_address,
+                    ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    wegld_token_id.clone(),
+                    min_amount_out,
+                )
+                    .into(),
+            );
+            let wegld_tokens = self.call_router_swap(fee_tokens.clone(), swap_operations);
+            if wegld_tokens.token_identifier != wegld_token_id {
+                remaining_tokens.push(wegld_tokens);
+                continue;
+            }
+
+            self.emit_keeper_compensation_event(keeper, &fee_tokens, &wegld_tokens.amount);
+            total_wegld += wegld_tokens.amount;
+        }
+
+        if !remaining_tokens.is_empty() {
+            pending_mapper.set(UniquePayments::new_from_unique_payments(remaining_tokens));
+        }
+
+        if !treasury_tokens.is_empty() {
+            let locked_token_id = self.get_locked_token_id();
+            let treasury_address = self.treasury_address().get();
+            self.add_fees(
+                keeper,
+                &locked_token_id,
+                treasury_address,
+                self.accumulated_fees(),
+                treasury_tokens,
+            );
+        }
+
+        if total_wegld == 0 {
+            return;
+        }
+
+        let egld_amount =
+            self.call_unwrap_egld(EsdtTokenPayment::new(wegld_token_id, 0, total_wegld));
+        self.keeper_egld_compensation(keeper)
+            .update(|total| *total += &egld_amount);
+        self.send().direct_egld(keeper, &egld_amount);
+    }
+
+    fn is_compensation_pair(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        wegld_token_id: &TokenIdentifier,
+    ) -> bool {
+        let first_token_id = self.get_first_token_id_mapper(pair_address.clone()).get();
+        let second_token_id = self.get_second_token_id_mapper(pair_address.clone()).get();
+
+        (&first_token_id == token_id && &second_token_id == wegld_token_id)
+            || (&first_token_id == wegld_token_id && &second_token_id == token_id)
+    }
+
+    #[allow(deprecated)]
+    fn call_unwrap_egld(&self, wegld_tokens: EsdtTokenPayment) -> BigUint {
+        let wrapper_sc_address = self.egld_wrapper_address().get();
+        let ((), back_transfers) = self
+            .egld_wrapper_proxy(wrapper_sc_address)
+            .unwrap_egld()
+            .with_esdt_transfer(wegld_tokens)
+            .execute_on_dest_context_with_back_transfers();
+
+        back_transfers.total_egld_amount
+    }
+
+    #[view(getKeeperCompensationWegldTokenId)]
+    #[storage_mapper("keeperCompensationWegldTokenId")]
+    fn keeper_compensation_wegld_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getKeeperCompensationMaxSlippage)]
+    #[storage_mapper("keeperCompensationMaxSlippage")]
+    fn keeper_compensation_max_slippage(&self) -> SingleValueMapper<u64>;
+
+    /// Total EGLD received by the keeper from fee conversions
+    #[view(getKeeperEgldCompensation)]
+    #[storage_mapper("keeperEgldCompensation")]
+    fn keeper_egld_compensation(&self, keeper: &ManagedAddress) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,304 @@
+multiversx_sc::imports!();
+
+use auto_pos_creator::external_sc_interactions::pair_actions::{PairAddLiqArgs, PairAddLiqResult};
+use common_structs::PaymentsVec;
+use pair::pair_actions::swap::ProxyTrait as _;
+use pair::safe_price_view::ProxyTrait as _;
+
+use crate::common::common_storage::MAX_PERCENTAGE;
+
+pub const PAIR_MAX_PERCENTAGE: u64 = 100_000;
+
+#[multiversx_sc::module]
+pub trait LpFarmCompoundingModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Sets the maximum accepted slippage when compounding rewards into LP farms.
+    /// Must be a value between 0 and 10_000, where 10_000 is 100%.
+    /// A value of 0 disables LP farm compounding.
+    #[only_owner]
+    #[endpoint(setLpCompoundMaxSlippage)]
+    fn set_lp_compound_max_slippage(&self, max_slippage: u64) {
+        require!(max_slippage < MAX_PERCENTAGE, "Invalid slippage");
+
+        self.lp_compound_max_slippage().set(max_slippage);
+    }
+
+    /// Returns the tokens that were not used when adding liquidity
+    fn compound_rewards_with_existing_lp_farm_position(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        new_tokens: EsdtTokenPayment,
+    ) -> Result<PaymentsVec<Self::Api>, ()> {
+        let max_slippage = self.lp_compound_max_slippage().get();
+        if max_slippage == 0 || new_tokens.token_nonce != 0 || new_tokens.amount < 2u64 {
+            return Result::Err(());
+        }
+
+        let farms_mapper = self.farm_ids();
+        for farm_id in self.user_farm_ids(user_id).iter() {
+            let opt_farm_addr = farms_mapper.get_address(farm_id);
+            if opt_farm_addr.is_none() {
+                continue;
+            }
+
+            let farm_addr = unsafe { opt_farm_addr.unwrap_unchecked() };
+            if !self.can_compound_into(user_id, &farm_addr) {
+                continue;
+            }
+
+            let pair_addr_mapper = self.get_farm_pair_contract_address_mapper(farm_addr.clone());
+            if pair_addr_mapper.is_empty() {
+                continue;
+            }
+
+            let pair_address = pair_addr_mapper.get();
+            let pair_config = self.get_pair_config(&pair_address);
+            let is_first_token = new_tokens.token_identifier == pair_config.first_token_id;
+            let other_token_id = if is_first_token {
+                pair_config.second_token_id
+            } else if new_tokens.token_identifier == pair_config.second_token_id {
+                pair_config.first_token_id
+            } else {
+                continue;
+            };
+
+            let opt_add_liq_result = self.add_liquidity_single_token(
+                pair_address,
+                new_tokens,
+                is_first_token,
+                other_token_id,
+                max_slippage,
+            );
+            if opt_add_liq_result.is_none() {
+                return Result::Err(());
+            }
+
+            let add_liq_result = unsafe { opt_add_liq_result.unwrap_unchecked() };
+            self.add_compounded_usage(user_id, &farm_addr, &add_liq_result.lp_tokens.amount);
+            let positions_mapper = self.user_farm_positions(user_id, farm_id);
+            let mut positions = positions_mapper.get();
+            let existing_farm_pos = positions.get(0).clone();
+            let new_farm_token = self.call_enter_farm_with_additional_tokens(
+                farm_addr,
+                user.clone(),
+                existing_farm_pos,
+                add_liq_result.lp_tokens,
+            );
+            let _ = positions.set(0, new_farm_token);
+            positions_mapper.set(&positions);
+
+            return Result::Ok(self.get_add_liquidity_leftovers(add_liq_result));
+        }
+
+        Result::Err(())
+    }
+
+    /// Swaps half of the tokens for the other token of the pair, and adds liquidity with both.
+    ///
+    /// Returns `None` if the price impact of the swap is above the max slippage,
+    /// or if adding liquidity after the swap would not meet the minimum amounts
+    fn add_liquidity_single_token(
+        &self,
+        pair_address: ManagedAddress,
+        new_tokens: EsdtTokenPayment,
+        is_first_token: bool,
+        other_token_id: TokenIdentifier,
+        max_slippage: u64,
+    ) -> Option<PairAddLiqResult<Self::Api>> {
+        let swap_amount = &new_tokens.amount / 2u64;
+        let kept_amount = &new_tokens.amount - &swap_amount;
+        let swap_min_amount_out = self.get_safe_swap_min_amount_out(
+            &pair_address,
+            &new_tokens.token_identifier,
+            &other_token_id,
+            &swap_amount,
+            max_slippage,
+        )?;
+        if !self.can_add_liquidity_after_swap(
+            &pair_address,
+            &new_tokens.token_identifier,
+            &other_token_id,
+            &swap_amount,
+            &kept_amount,
+            max_slippage,
+        ) {
+            return None;
+        }
+
+        let kept_tokens =
+            EsdtTokenPayment::new(new_tokens.token_identifier.clone(), 0, kept_amount);
+        let swap_input_tokens = EsdtTokenPayment::new(new_tokens.token_identifier, 0, swap_amount);
+        let swapped_tokens: EsdtTokenPayment = self
+            .pair_proxy(pair_address.clone())
+            .swap_tokens_fixed_input(other_token_id, swap_min_amount_out)
+            .with_esdt_transfer(swap_input_tokens)
+            .execute_on_dest_context();
+
+        let (first_tokens, second_tokens) = if is_first_token {
+            (kept_tokens, swapped_tokens)
+        } else {
+            (swapped_tokens, kept_tokens)
+        };
+        let first_token_min_amount_out =
+            self.apply_max_slippage(&first_tokens.amount, max_slippage);
+        let second_token_min_amount_out =
+            self.apply_max_slippage(&second_tokens.amount, max_slippage);
+        let add_liq_result = self.call_pair_add_liquidity(PairAddLiqArgs {
+            pair_address,
+            first_tokens,
+            second_tokens,
+            first_token_min_amount_out,
+            second_token_min_amount_out,
+        });
+
+        Some(add_liq_result)
+    }
+
+    /// Predicts the pair's reserves after swapping swap_amount of token_in,
+    /// and checks that adding liquidity with the kept and swapped tokens meets the minimum amounts.
+    /// The pair's add liquidity only keeps the amounts matching its ratio,
+    /// and fails if either of them is below the given minimum.
+    fn can_add_liquidity_after_swap(
+        &self,
+        pair_address: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        swap_amount: &BigUint,
+        kept_amount: &BigUint,
+        max_slippage: u64,
+    ) -> bool {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), token_in)
+            .get();
+        let reserve_out = self
+            .get_pair_reserve_mapper(pair_address.clone(), token_out)
+            .get();
+        let swapped_amount =
+            self.get_swap_amount_out(pair_address, &reserve_in, &reserve_out, swap_amount);
+        if swapped_amount == 0 || swapped_amount >= reserve_out {
+            return false;
+        }
+
+        let special_fee_percent = self
+            .get_pair_special_fee_percent_mapper(pair_address.clone())
+            .get();
+        let special_fee_amount = swap_amount * special_fee_percent / PAIR_MAX_PERCENTAGE;
+        let new_reserve_in = reserve_in + swap_amount - special_fee_amount;
+        let new_reserve_out = reserve_out - &swapped_amount;
+
+        let kept_min_amount = self.apply_max_slippage(kept_amount, max_slippage);
+        let swapped_min_amount = self.apply_max_slippage(&swapped_amount, max_slippage);
+        if kept_min_amount == 0 || swapped_min_amount == 0 {
+            return false;
+        }
+
+        let optimal_swapped_amount = kept_amount * &new_reserve_out / &new_reserve_in;
+        if optimal_swapped_amount <= swapped_amount {
+            return optimal_swapped_amount >= swapped_min_amount;
+        }
+
+        let optimal_kept_amount = &swapped_amount * &new_reserve_in / &new_reserve_out;
+        optimal_kept_amount >= kept_min_amount
+    }
+
+    fn get_add_liquidity_leftovers(
+        &self,
+        add_liq_result: PairAddLiqResult<Self::Api>,
+    ) -> PaymentsVec<Self::Api> {
+        let mut leftover_tokens = PaymentsVec::new();
+        if add_liq_result.first_tokens_remaining.amount > 0 {
+            leftover_tokens.push(add_liq_result.first_tokens_remaining);
+        }
+        if add_liq_result.second_tokens_remaining.amount > 0 {
+            leftover_tokens.push(add_liq_result.second_tokens_remaining);
+        }
+
+        leftover_tokens
+    }
+
+    /// The minimum output is derived from the pair's safe price, which cannot be moved
+    /// within the same block.
+    ///
+    /// Returns `None` if the swap output at the current reserves is below that minimum,
+    /// i.e. the price impact or the deviation from the safe price is above the max slippage
+    fn get_safe_swap_min_amount_out(
+        &self,
+        pair_address: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        amount_in: &BigUint,
+        max_slippage: u64,
+    ) -> Option<BigUint> {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), token_in)
+            .get();
+        let reserve_out = self
+            .get_pair_reserve_mapper(pair_address.clone(), token_out)
+            .get();
+        if reserve_in == 0 || reserve_out == 0 {
+            return None;
+        }
+
+        let amount_out =
+            self.get_swap_amount_out(pair_address, &reserve_in, &reserve_out, amount_in);
+        let safe_price_payment: EsdtTokenPayment = self
+            .pair_proxy(pair_address.clone())
+            .get_safe_price_by_default_offset(
+                pair_address.clone(),
+                EsdtTokenPayment::new(token_in.clone(), 0, amount_in.clone()),
+            )
+            .execute_on_dest_context();
+        if &safe_price_payment.token_identifier != token_out {
+            return None;
+        }
+
+        let min_amount_out = self.apply_max_slippage(&safe_price_payment.amount, max_slippage);
+        if min_amount_out == 0 || amount_out < min_amount_out {
+            return None;
+        }
+
+        Some(min_amount_out)
+    }
+
+    /// Same formula as the pair's fixed input swap
+    fn get_swap_amount_out(
+        &self,
+        pair_address: &ManagedAddress,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        amount_in: &BigUint,
+    ) -> BigUint {
+        let total_fee_percent = self
+            .get_pair_total_fee_percent_mapper(pair_address.clone())
+            .get();
+        let amount_in_with_fee = amount_in * (PAIR_MAX_PERCENTAGE - total_fee_percent);
+
+        &amount_in_with_fee * reserve_out / (reserve_in * PAIR_MAX_PERCENTAGE + &amount_in_with_fee)
+    }
+
+    fn apply_max_slippage(&self, amount: &BigUint, max_slippage: u64) -> BigUint {
+        amount * (MAX_PERCENTAGE - max_slippage) / MAX_PERCENTAGE
+    }
+
+    #[view(getLpCompoundMaxSlippage)]
+    #[storage_mapper("lpCompoundMaxSlippage")]
+    fn lp_compound_max_slippage(&self) -> SingleValueMapper<u64>;
+}
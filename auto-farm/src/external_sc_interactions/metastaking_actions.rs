@@ -30,32 +30,32 @@ pub trait MetastakingActionsModule:
         rew_wrapper: &mut RewardsWrapper<Self::Api>,
     ) {
         let ms_mapper = self.metastaking_ids();
-        let user_tokens_mapper = self.user_metastaking_tokens(user_id);
-        let user_dual_yield_tokens = user_tokens_mapper.get();
-        if user_dual_yield_tokens.is_empty() {
-            return;
-        }
-
-        let mut new_user_dual_yield_tokens = PaymentsVec::new();
-        for dual_yield_token in user_dual_yield_tokens.iter() {
-            let ms_id = self
-                .metastaking_for_dual_yield_token(&dual_yield_token.token_identifier)
-                .get();
+        let user_ms_ids: ManagedVec<Self::Api, AddressId> =
+            self.user_metastaking_ids(user_id).iter().collect();
+        for ms_id in user_ms_ids.iter() {
             let opt_ms_addr = ms_mapper.get_address(ms_id);
             if opt_ms_addr.is_none() {
-                new_user_dual_yield_tokens.push(dual_yield_token.clone());
                 continue;
             }
 
             let ms_addr = unsafe { opt_ms_addr.unwrap_unchecked() };
-            let claim_result = self.call_metastaking_claim(ms_addr, user.clone(), dual_yield_token.clone());
-            new_user_dual_yield_tokens.push(claim_result.new_dual_yield_tokens);
+            let positions_mapper = self.user_metastaking_positions(user_id, ms_id);
+            let user_dual_yield_tokens = positions_mapper.get();
+            let mut new_user_dual_yield_tokens = PaymentsVec::new();
+            for dual_yield_token in user_dual_yield_tokens.iter() {
+                let claim_result = self.call_metastaking_claim(
+                    ms_addr.clone(),
+                    user.clone(),
+                    dual_yield_token.clone(),
+                );
+                new_user_dual_yield_tokens.push(claim_result.new_dual_yield_tokens);
 
-            rew_wrapper.add_tokens(claim_result.lp_farm_rewards);
-            rew_wrapper.add_tokens(claim_result.staking_farm_rewards);
-        }
+                rew_wrapper.add_tokens(claim_result.lp_farm_rewards);
+                rew_wrapper.add_tokens(claim_result.staking_farm_rewards);
+            }
 
-        user_tokens_mapper.set(&new_user_dual_yield_tokens);
+            positions_mapper.set(&new_user_dual_yield_tokens);
+        }
     }
 
     fn call_metastaking_claim(
@@ -0,0 +1,147 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+use farm::EnterFarmResultType;
+use farm_staking_proxy::proxy_actions::stake::ProxyTrait as _;
+use farm_staking_proxy::result_types::StakeProxyResult;
+
+use crate::common::rewards_wrapper::RewardsWrapper;
+
+#[multiversx_sc::module]
+pub trait MetastakingCompoundingModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Adds liquidity with the new tokens in the pair of the metastaking's LP farm,
+    /// enters the LP farm, and stakes the LP farm tokens merged with the user's dual yield tokens.
+    /// Uses the same max slippage as LP farm compounding.
+    ///
+    /// The boosted rewards received from the LP farm and metastaking are added to `boosted_rewards`.
+    ///
+    /// Returns the tokens that were not used when adding liquidity
+    fn compound_rewards_with_existing_metastaking_position(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        new_tokens: EsdtTokenPayment,
+        boosted_rewards: &mut RewardsWrapper<Self::Api>,
+    ) -> Result<PaymentsVec<Self::Api>, ()> {
+        let max_slippage = self.lp_compound_max_slippage().get();
+        if max_slippage == 0 || new_tokens.token_nonce != 0 || new_tokens.amount < 2u64 {
+            return Result::Err(());
+        }
+
+        let ms_mapper = self.metastaking_ids();
+        for ms_id in self.user_metastaking_ids(user_id).iter() {
+            let opt_ms_addr = ms_mapper.get_address(ms_id);
+            if opt_ms_addr.is_none() {
+                continue;
+            }
+
+            let ms_addr = unsafe { opt_ms_addr.unwrap_unchecked() };
+            if !self.can_compound_into(user_id, &ms_addr) {
+                continue;
+            }
+
+            let lp_farm_addr = self.get_lp_farm_address_mapper(ms_addr.clone()).get();
+            let pair_addr_mapper = self.get_farm_pair_contract_address_mapper(lp_farm_addr.clone());
+            if pair_addr_mapper.is_empty() {
+                continue;
+            }
+
+            let pair_address = pair_addr_mapper.get();
+            let pair_config = self.get_pair_config(&pair_address);
+            let is_first_token = new_tokens.token_identifier == pair_config.first_token_id;
+            let other_token_id = if is_first_token {
+                pair_config.second_token_id
+            } else if new_tokens.token_identifier == pair_config.second_token_id {
+                pair_config.first_token_id
+            } else {
+                continue;
+            };
+
+            let opt_add_liq_result = self.add_liquidity_single_token(
+                pair_address,
+                new_tokens,
+                is_first_token,
+                other_token_id,
+                max_slippage,
+            );
+            if opt_add_liq_result.is_none() {
+                return Result::Err(());
+            }
+
+            let add_liq_result = unsafe { opt_add_liq_result.unwrap_unchecked() };
+            self.add_compounded_usage(user_id, &ms_addr, &add_liq_result.lp_tokens.amount);
+            let (lp_farm_token, lp_farm_boosted_rewards) = self.call_enter_lp_farm(
+                lp_farm_addr,
+                user.clone(),
+                add_liq_result.lp_tokens.clone(),
+            );
+            boosted_rewards.add_tokens(lp_farm_boosted_rewards);
+
+            let positions_mapper = self.user_metastaking_positions(user_id, ms_id);
+            let mut positions = positions_mapper.get();
+            let mut stake_payments = PaymentsVec::from_single_item(lp_farm_token);
+            stake_payments.push(positions.get(0).clone());
+            let stake_result =
+                self.call_metastaking_compound_stake(ms_addr, user.clone(), stake_payments);
+            let _ = positions.set(0, stake_result.dual_yield_tokens);
+            positions_mapper.set(&positions);
+
+            boosted_rewards.add_tokens(stake_result.lp_farm_boosted_rewards);
+            boosted_rewards.add_tokens(stake_result.staking_boosted_rewards);
+
+            return Result::Ok(self.get_add_liquidity_leftovers(add_liq_result));
+        }
+
+        Result::Err(())
+    }
+
+    fn call_enter_lp_farm(
+        &self,
+        lp_farm_addr: ManagedAddress,
+        user: ManagedAddress,
+        lp_tokens: EsdtTokenPayment,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        let raw_results: EnterFarmResultType<Self::Api> = self
+            .farm_proxy(lp_farm_addr)
+            .enter_farm_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(lp_tokens)
+            .execute_on_dest_context();
+
+        raw_results.into_tuple()
+    }
+
+    fn call_metastaking_compound_stake(
+        &self,
+        ms_address: ManagedAddress,
+        user: ManagedAddress,
+        payments: PaymentsVec<Self::Api>,
+    ) -> StakeProxyResult<Self::Api> {
+        self.metastaking_proxy(ms_address)
+            .stake_farm_tokens(OptionalValue::Some(user))
+            .with_multi_token_transfer(payments)
+            .execute_on_dest_context()
+    }
+}
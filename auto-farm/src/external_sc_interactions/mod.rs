@@ -1,5 +1,14 @@
+pub mod energy_compounding;
+pub mod energy_update;
 pub mod farm_actions;
+pub mod farm_migration;
 pub mod fees_collector_actions;
+pub mod keeper_compensation;
 pub mod locked_token_merging;
+pub mod lp_farm_compounding;
 pub mod metastaking_actions;
+pub mod metastaking_compounding;
 pub mod multi_contract_interactions;
+pub mod position_creation;
+pub mod proxy_dex_actions;
+pub mod users_processing;
@@ -1,6 +1,15 @@
 multiversx_sc::imports!();
 
-use crate::common::{rewards_wrapper::RewardsWrapper, unique_payments::UniquePayments};
+use common_structs::PaymentsVec;
+
+use crate::{
+    common::{
+        rewards_wrapper::{MergedRewardsWrapper, RewardsWrapper},
+        unique_payments::UniquePayments,
+    },
+    events::WithdrawType,
+    user_tokens::user_strategy::CompoundStrategy,
+};
 
 #[multiversx_sc::module]
 pub trait MultiContractInteractionsModule:
@@ -11,76 +20,206 @@ pub trait MultiContractInteractionsModule:
     + crate::registration::RegistrationModule
     + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
     + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_migration::FarmMigrationModule
     + crate::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule
     + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
     + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
     + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
     + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule
+    + crate::user_tokens::user_wrapped_farm_tokens::UserWrappedFarmTokensModule
+    + crate::external_sc_interactions::proxy_dex_actions::ProxyDexActionsModule
+    + crate::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule
+    + crate::external_sc_interactions::metastaking_compounding::MetastakingCompoundingModule
+    + crate::external_sc_interactions::keeper_compensation::KeeperCompensationModule
+    + crate::external_sc_interactions::energy_compounding::EnergyCompoundingModule
+    + crate::external_sc_interactions::energy_update::EnergyUpdateModule
     + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::user_payouts::UserPayoutsModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::user_tokens::user_strategy::UserStrategyModule
     + crate::fees::FeesModule
     + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
     + energy_query::EnergyQueryModule
     + utils::UtilsModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + auto_pos_creator::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + auto_pos_creator::external_sc_interactions::router_actions::RouterActionsModule
 {
-    /// Claims rewards from fees collector, and farms
-    /// Then, compounds rewards into farms where possible
+    /// Claims rewards from fees collector, farms, metastaking and proxy-dex
+    /// Then, handles the rewards according to each user's compound strategy,
+    /// and updates the user's energy in all the whitelisted contracts
+    ///
+    /// Users that only deposited locked tokens are only claimed from fees collector
+    ///
+    /// At the end of the batch, the caller's pending keeper compensation fees are converted to EGLD
+    /// and sent to the caller
     ///
     /// Args: Pairs of user to claim for
     #[endpoint(claimAllRewardsAndCompound)]
     fn claim_all_rewards_and_compound(&self, users: MultiValueEncoded<ManagedAddress>) {
-        self.require_caller_proxy_claim_address();
+        self.require_caller_keeper();
+        self.require_active();
 
         let locked_token_id = self.get_locked_token_id();
+        let mut processed_users = 0;
         for user in users {
             self.claim_all_single_user(&user, locked_token_id.clone());
+            processed_users += 1;
+        }
+
+        let caller = self.blockchain().get_caller();
+        if processed_users > 0 {
+            self.convert_keeper_compensation(&caller);
         }
+        self.emit_batch_processed_event(&caller, processed_users);
     }
 
     fn claim_all_single_user(&self, user: &ManagedAddress, locked_token_id: TokenIdentifier) {
         let user_id = self.user_ids().get_id_non_zero(user);
         let mut rew_wrapper = RewardsWrapper::new(locked_token_id);
+        self.migrate_legacy_user_farm_tokens(user_id);
+        self.migrate_legacy_user_metastaking_tokens(user_id);
 
         self.claim_fees_collector_rewards(user, &mut rew_wrapper);
         self.claim_all_farm_rewards(user, user_id, &mut rew_wrapper);
+        self.migrate_farm_positions(user, user_id, &mut rew_wrapper);
         self.claim_all_metastaking_rewards(user, user_id, &mut rew_wrapper);
+        self.claim_all_wrapped_farm_rewards(user, user_id, &mut rew_wrapper);
 
         self.add_user_rewards(user.clone(), user_id, rew_wrapper);
+        self.compound_locked_rewards_into_energy(user, user_id);
+
+        let strategy = self.user_strategy(user_id).get();
+        match strategy {
+            CompoundStrategy::ClaimOnly => {}
+            CompoundStrategy::CompoundIntoStaking
+            | CompoundStrategy::CompoundIntoLpFarm
+            | CompoundStrategy::CompoundIntoMetastaking => {
+                self.compound_user_rewards(user, user_id, strategy);
+            }
+            CompoundStrategy::SendToWallet => {
+                let sent_rewards = self.user_claim_rewards(user.clone(), user_id);
+                self.emit_token_withdrawal_event(user, WithdrawType::RewardTokens, &sent_rewards);
+            }
+        }
+        self.process_auto_payouts(user, user_id);
+
+        let energy_updated_contracts = self.update_energy_all_contracts(user, user_id);
 
         let user_rewards_mapper = self.user_rewards(user_id);
-        let user_farm_tokens_mapper = self.user_farm_tokens(user_id);
+        let user_wrapped_rewards = if !user_rewards_mapper.is_empty() {
+            user_rewards_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        };
+        let user_farm_tokens = self.get_all_user_farm_tokens(user_id);
+
+        self.emit_proxy_claim_event(
+            user,
+            strategy,
+            &user_wrapped_rewards,
+            &user_farm_tokens,
+            &energy_updated_contracts,
+        );
+    }
 
+    fn compound_user_rewards(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        strategy: CompoundStrategy,
+    ) {
+        let user_rewards_mapper = self.user_rewards(user_id);
         let mut user_wrapped_rewards = user_rewards_mapper.get();
         let mut user_rewards = user_wrapped_rewards.other_tokens.into_payments();
-        let mut user_farm_tokens = user_farm_tokens_mapper.get();
-        let user_farm_ids = self.get_farm_ids_for_farm_tokens(&user_farm_tokens);
 
+        let mut leftover_tokens = PaymentsVec::new();
+        let mut boosted_rewards = RewardsWrapper::new(self.get_locked_token_id());
         let mut i = 0;
         let mut len = user_rewards.len();
         while i < len {
             let current_payment = user_rewards.get(i).clone();
-            let compound_result = self.compound_staking_rewards_with_existing_farm_position(
+            let compound_result = self.compound_single_reward(
                 user,
-                &mut user_farm_tokens,
-                &user_farm_ids,
-                current_payment,
+                user_id,
+                strategy,
+                current_payment.clone(),
+                &mut boosted_rewards,
             );
-            if compound_result.is_err() {
-                i += 1;
-                continue;
+            match compound_result {
+                Result::Ok(leftovers) => {
+                    let mut compounded_payment = current_payment;
+                    for leftover in leftovers.iter() {
+                        if leftover.token_identifier == compounded_payment.token_identifier {
+                            compounded_payment.amount -= &leftover.amount;
+                        }
+                    }
+                    self.record_compounded_rewards(user_id, &compounded_payment);
+
+                    leftover_tokens.append_vec(leftovers);
+                    user_rewards.remove(i);
+                    len -= 1;
+                }
+                Result::Err(()) => {
+                    i += 1;
+                }
             }
+        }
 
-            user_rewards.remove(i);
-            len -= 1;
+        let mut remaining_rewards = UniquePayments::new_from_unique_payments(user_rewards);
+        for leftover in leftover_tokens.iter() {
+            remaining_rewards.add_payment(leftover.clone());
         }
 
-        user_wrapped_rewards.other_tokens = UniquePayments::new_from_unique_payments(user_rewards);
+        user_wrapped_rewards.other_tokens = remaining_rewards;
         user_rewards_mapper.set(&user_wrapped_rewards);
-        user_farm_tokens_mapper.set(&user_farm_tokens);
 
-        self.emit_proxy_claim_event(user, &user_wrapped_rewards, &user_farm_tokens);
+        if !boosted_rewards.is_empty() {
+            self.add_user_rewards(user.clone(), user_id, boosted_rewards);
+        }
+    }
+
+    /// Returns the tokens left over after compounding.
+    /// Boosted rewards received while compounding are added to `boosted_rewards`
+    fn compound_single_reward(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        strategy: CompoundStrategy,
+        payment: EsdtTokenPayment,
+        boosted_rewards: &mut RewardsWrapper<Self::Api>,
+    ) -> Result<PaymentsVec<Self::Api>, ()> {
+        if strategy == CompoundStrategy::CompoundIntoMetastaking {
+            let ms_compound_result = self.compound_rewards_with_existing_metastaking_position(
+                user,
+                user_id,
+                payment.clone(),
+                boosted_rewards,
+            );
+            if ms_compound_result.is_ok() {
+                return ms_compound_result;
+            }
+        }
+
+        if strategy == CompoundStrategy::CompoundIntoLpFarm {
+            let lp_compound_result = self.compound_rewards_with_existing_lp_farm_position(
+                user,
+                user_id,
+                payment.clone(),
+            );
+            if lp_compound_result.is_ok() {
+                return lp_compound_result;
+            }
+        }
+
+        self.compound_staking_rewards_with_existing_farm_position(user, user_id, payment)?;
+
+        Result::Ok(PaymentsVec::new())
     }
 }
@@ -0,0 +1,186 @@
+multiversx_sc::imports!();
+
+use auto_pos_creator::{
+    external_sc_interactions::router_actions::SwapOperationType,
+    multi_contract_interactions::create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs},
+};
+use common_structs::PaymentsVec;
+
+use crate::events::DepositType;
+
+#[multiversx_sc::module]
+pub trait PositionCreationModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + utils::UtilsModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + auto_pos_creator::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + auto_pos_creator::external_sc_interactions::router_actions::RouterActionsModule
+    + auto_pos_creator::external_sc_interactions::farm_actions::FarmActionsModule
+    + auto_pos_creator::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + auto_pos_creator::multi_contract_interactions::create_pos::CreatePosModule
+{
+    /// Addresses used to wrap EGLD payments and to swap the input token
+    /// before creating a position through makeMeAPosition
+    #[only_owner]
+    #[endpoint(setPositionCreationAddresses)]
+    fn set_position_creation_addresses(
+        &self,
+        egld_wrapper_address: ManagedAddress,
+        router_address: ManagedAddress,
+    ) {
+        self.require_sc_address(&egld_wrapper_address);
+        self.require_sc_address(&router_address);
+
+        self.egld_wrapper_address().set(egld_wrapper_address);
+        self.router_address().set(router_address);
+    }
+
+    /// Creates a farm or metastaking position from a single token (or EGLD),
+    /// and deposits the new position in the caller's name.
+    ///
+    /// target_address: A whitelisted farm or metastaking address.
+    /// swap_min_amount_out: Slippage limit for swapping half of the input into the pair's other token.
+    ///     Must be greater than 0 when that swap happens.
+    ///     Not used when the input is already the pair's LP token.
+    /// add_liq_first_token_min_amount_out/add_liq_second_token_min_amount_out:
+    ///     Slippage limits for adding liquidity in the target's pair.
+    /// swap_operations: Optional router swaps, used when the input token is not part of the pair.
+    ///
+    /// Returns the leftover tokens and the boosted rewards received when entering the position,
+    /// which are sent back to the caller.
+    #[payable("*")]
+    #[endpoint(makeMeAPosition)]
+    fn make_me_a_position(
+        &self,
+        target_address: ManagedAddress,
+        swap_min_amount_out: BigUint,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        self.require_active();
+        require!(
+            add_liq_first_token_min_amount_out > 0 && add_liq_second_token_min_amount_out > 0,
+            "Invalid slippage limits"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_or_insert(&caller);
+        let payment = self.call_value().egld_or_single_esdt();
+
+        let farm_id = self.farm_ids().get_id(&target_address);
+        let ms_id = self.metastaking_ids().get_id(&target_address);
+        let output_payments = if farm_id != NULL_ID {
+            let pair_address = self
+                .get_farm_pair_contract_address_mapper(target_address.clone())
+                .get();
+            let (first_token_payment, second_token_payment) = self.prepare_pair_tokens(
+                payment,
+                swap_operations,
+                &pair_address,
+                &swap_min_amount_out,
+            );
+
+            let args = CreateFarmPosArgs {
+                caller: caller.clone(),
+                first_token_payment,
+                second_token_payment,
+                additional_payments: PaymentsVec::new(),
+                add_liq_first_token_min_amount_out,
+                add_liq_second_token_min_amount_out,
+                pair_address,
+                farm_address: target_address,
+            };
+            let (new_farm_tokens, output_payments) = self.create_farm_pos(args);
+            self.add_user_farm_position(user_id, farm_id, new_farm_tokens.clone());
+            let new_position = PaymentsVec::from_single_item(new_farm_tokens);
+            self.add_deposited_positions_usage(user_id, &new_position);
+            self.emit_token_deposit_event(&caller, &caller, DepositType::FarmTokens, &new_position);
+
+            output_payments
+        } else if ms_id != NULL_ID {
+            let farm_address = self
+                .get_lp_farm_address_mapper(target_address.clone())
+                .get();
+            let pair_address = self
+                .get_farm_pair_contract_address_mapper(farm_address.clone())
+                .get();
+            let (first_token_payment, second_token_payment) = self.prepare_pair_tokens(
+                payment,
+                swap_operations,
+                &pair_address,
+                &swap_min_amount_out,
+            );
+
+            let args = CreateMetastakingPosArgs {
+                caller: caller.clone(),
+                first_token_payment,
+                second_token_payment,
+                additional_payments: PaymentsVec::new(),
+                add_liq_first_token_min_amount_out,
+                add_liq_second_token_min_amount_out,
+                pair_address,
+                farm_address,
+                metastaking_address: target_address,
+            };
+            let (new_dual_yield_tokens, output_payments) = self.create_metastaking_pos(args);
+            self.add_user_metastaking_position(user_id, ms_id, new_dual_yield_tokens.clone());
+            let new_position = PaymentsVec::from_single_item(new_dual_yield_tokens);
+            self.add_deposited_positions_usage(user_id, &new_position);
+            self.emit_token_deposit_event(
+                &caller,
+                &caller,
+                DepositType::MetastakingTokens,
+                &new_position,
+            );
+
+            output_payments
+        } else {
+            sc_panic!("Invalid target address");
+        };
+
+        output_payments.send_and_return(&caller)
+    }
+
+    fn prepare_pair_tokens(
+        &self,
+        payment: EgldOrEsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+        pair_address: &ManagedAddress,
+        swap_min_amount_out: &BigUint,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        self.require_sc_address(pair_address);
+
+        let mut first_token_payment = self.process_payment(payment, swap_operations);
+        let input_token_id = first_token_payment.token_identifier.clone();
+        let second_token_payment =
+            self.swap_half_input_payment_if_needed(&mut first_token_payment, pair_address.clone());
+
+        // the swapped tokens are first if the payments were reversed to match the pair's order
+        let swapped_tokens = if first_token_payment.token_identifier == input_token_id {
+            &second_token_payment
+        } else {
+            &first_token_payment
+        };
+        if swapped_tokens.token_identifier != input_token_id {
+            require!(*swap_min_amount_out > 0, "Invalid slippage limits");
+            require!(
+                &swapped_tokens.amount >= swap_min_amount_out,
+                "Swap output too low"
+            );
+        }
+
+        (first_token_payment, second_token_payment)
+    }
+}
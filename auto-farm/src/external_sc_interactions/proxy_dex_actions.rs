@@ -0,0 +1,105 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+use proxy_dex::{proxy_farm::ProxyTrait as _, wrapped_farm_attributes::WrappedFarmTokenAttributes};
+use read_external_storage::State;
+
+use crate::common::rewards_wrapper::RewardsWrapper;
+
+pub type ClaimRewardsProxyResultType<M> = MultiValue2<EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+
+#[multiversx_sc::module]
+pub trait ProxyDexActionsModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule
+    + crate::user_tokens::user_wrapped_farm_tokens::UserWrappedFarmTokensModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + utils::UtilsModule
+{
+    /// The farm of each wrapped farm token must also be whitelisted
+    fn claim_all_wrapped_farm_rewards(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+        rew_wrapper: &mut RewardsWrapper<Self::Api>,
+    ) {
+        let proxy_dex_mapper = self.proxy_dex_ids();
+        let farms_mapper = self.farm_ids();
+        let user_tokens_mapper = self.user_wrapped_farm_tokens(user_id);
+        let user_wrapped_farm_tokens = user_tokens_mapper.get();
+        if user_wrapped_farm_tokens.is_empty() {
+            return;
+        }
+
+        let mut new_user_wrapped_farm_tokens = PaymentsVec::new();
+        for wrapped_farm_token in user_wrapped_farm_tokens.iter() {
+            let proxy_dex_id = self
+                .proxy_dex_for_wrapped_farm_token(&wrapped_farm_token.token_identifier)
+                .get();
+            let opt_proxy_dex_addr = proxy_dex_mapper.get_address(proxy_dex_id);
+            if opt_proxy_dex_addr.is_none() {
+                new_user_wrapped_farm_tokens.push(wrapped_farm_token.clone());
+                continue;
+            }
+
+            let attributes: WrappedFarmTokenAttributes<Self::Api> =
+                self.blockchain().get_token_attributes(
+                    &wrapped_farm_token.token_identifier,
+                    wrapped_farm_token.token_nonce,
+                );
+            let farm_id = self
+                .farm_for_farm_token(&attributes.farm_token.token_identifier)
+                .get();
+            let opt_farm_addr = farms_mapper.get_address(farm_id);
+            if opt_farm_addr.is_none() {
+                new_user_wrapped_farm_tokens.push(wrapped_farm_token.clone());
+                continue;
+            }
+
+            let farm_addr = unsafe { opt_farm_addr.unwrap_unchecked() };
+            let farm_state = self.get_farm_state(farm_addr.clone());
+            if farm_state != State::Active {
+                new_user_wrapped_farm_tokens.push(wrapped_farm_token.clone());
+                continue;
+            }
+
+            let proxy_dex_addr = unsafe { opt_proxy_dex_addr.unwrap_unchecked() };
+            let (new_wrapped_farm_token, rewards) = self
+                .call_claim_rewards_proxy(
+                    proxy_dex_addr,
+                    farm_addr,
+                    user.clone(),
+                    wrapped_farm_token.clone(),
+                )
+                .into_tuple();
+            new_user_wrapped_farm_tokens.push(new_wrapped_farm_token);
+
+            if rewards.amount > 0 {
+                rew_wrapper.add_tokens(rewards);
+            }
+        }
+
+        user_tokens_mapper.set(&new_user_wrapped_farm_tokens);
+    }
+
+    fn call_claim_rewards_proxy(
+        &self,
+        proxy_dex_address: ManagedAddress,
+        farm_address: ManagedAddress,
+        user: ManagedAddress,
+        wrapped_farm_token: EsdtTokenPayment,
+    ) -> ClaimRewardsProxyResultType<Self::Api> {
+        self.proxy_dex_proxy(proxy_dex_address)
+            .claim_rewards_proxy(farm_address, OptionalValue::Some(user))
+            .with_esdt_transfer(wrapped_farm_token)
+            .execute_on_dest_context()
+    }
+
+    #[proxy]
+    fn proxy_dex_proxy(&self, sc_address: ManagedAddress) -> proxy_dex::Proxy<Self::Api>;
+}
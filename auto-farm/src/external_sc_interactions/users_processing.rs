@@ -0,0 +1,118 @@
+multiversx_sc::imports!();
+
+use common_structs::Epoch;
+
+#[multiversx_sc::module]
+pub trait UsersProcessingModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::registration::RegistrationModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule
+    + crate::external_sc_interactions::energy_compounding::EnergyCompoundingModule
+    + crate::external_sc_interactions::energy_update::EnergyUpdateModule
+    + crate::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule
+    + crate::external_sc_interactions::keeper_compensation::KeeperCompensationModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::user_tokens::user_strategy::UserStrategyModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + auto_pos_creator::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + auto_pos_creator::external_sc_interactions::router_actions::RouterActionsModule
+{
+    #[only_owner]
+    #[endpoint(setMinGasForProcessing)]
+    fn set_min_gas_for_processing(&self, min_gas: u64) {
+        self.min_gas_for_processing().set(min_gas);
+    }
+
+    /// Processes at most max_users registered users, continuing from the last processed user.
+    /// Processing stops early if the remaining gas drops below the configured threshold,
+    /// or once the last registered user was processed, in which case a new cycle starts.
+    /// The full cycle epoch is only recorded if the batch processed at least one user.
+    /// If any user was processed, the caller's pending keeper compensation is converted to EGLD.
+    ///
+    /// Returns the number of processed users
+    #[endpoint(processNextUsers)]
+    fn process_next_users(&self, max_users: usize) -> usize {
+        self.require_caller_keeper();
+        self.require_active();
+
+        let ids_mapper = self.user_ids();
+        let last_user_id = ids_mapper.get_last_id();
+        let min_gas = self.min_gas_for_processing().get();
+        let locked_token_id = self.get_locked_token_id();
+        let cursor_mapper = self.processing_cursor();
+        let mut cursor = cursor_mapper.get();
+        let mut processed_users = 0;
+        while processed_users < max_users && cursor < last_user_id {
+            let gas_left = self.blockchain().get_gas_left();
+            if gas_left < min_gas {
+                break;
+            }
+
+            cursor += 1;
+
+            if let Some(user) = ids_mapper.get_address(cursor) {
+                self.claim_all_single_user(&user, locked_token_id.clone());
+                processed_users += 1;
+            }
+        }
+
+        if cursor >= last_user_id {
+            cursor = NULL_ID;
+            if processed_users > 0 {
+                self.last_full_cycle_epoch()
+                    .set(self.blockchain().get_block_epoch());
+            }
+        }
+
+        cursor_mapper.set(cursor);
+
+        let caller = self.blockchain().get_caller();
+        if processed_users > 0 {
+            self.convert_keeper_compensation(&caller);
+        }
+        self.emit_batch_processed_event(&caller, processed_users);
+
+        processed_users
+    }
+
+    /// Returns the last processed user ID and the last registered user ID
+    #[view(getProcessingProgress)]
+    fn get_processing_progress(&self) -> MultiValue2<AddressId, AddressId> {
+        let cursor = self.processing_cursor().get();
+        let last_user_id = self.user_ids().get_last_id();
+
+        (cursor, last_user_id).into()
+    }
+
+    #[view(getProcessingCursor)]
+    #[storage_mapper("processingCursor")]
+    fn processing_cursor(&self) -> SingleValueMapper<AddressId>;
+
+    #[view(getLastFullCycleEpoch)]
+    #[storage_mapper("lastFullCycleEpoch")]
+    fn last_full_cycle_epoch(&self) -> SingleValueMapper<Epoch>;
+
+    #[view(getMinGasForProcessing)]
+    #[storage_mapper("minGasForProcessing")]
+    fn min_gas_for_processing(&self) -> SingleValueMapper<u64>;
+}
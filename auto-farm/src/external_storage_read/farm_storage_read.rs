@@ -36,4 +36,25 @@ pub trait FarmStorageReadModule:
     fn get_farm_state(&self, farm_address: ManagedAddress) -> State {
         self.get_farm_state_mapper(farm_address).get()
     }
+
+    /// Estimates the base rewards of a farm position, using the last reward per share
+    /// stored in the farm. Boosted rewards are not included.
+    fn estimate_farm_position_rewards(
+        &self,
+        farm_address: ManagedAddress,
+        token_reward_per_share: &BigUint,
+        farm_token_amount: &BigUint,
+    ) -> EsdtTokenPayment {
+        let reward_token_id = self.get_reward_token_id_mapper(farm_address.clone()).get();
+        let farm_reward_per_share = self.get_reward_per_share_mapper(farm_address.clone()).get();
+        let division_safety_constant = self.get_division_safety_constant_mapper(farm_address).get();
+        if &farm_reward_per_share <= token_reward_per_share || division_safety_constant == 0 {
+            return EsdtTokenPayment::new(reward_token_id, 0, BigUint::zero());
+        }
+
+        let reward_per_share_diff = farm_reward_per_share - token_reward_per_share;
+        let reward_amount = farm_token_amount * &reward_per_share_diff / division_safety_constant;
+
+        EsdtTokenPayment::new(reward_token_id, 0, reward_amount)
+    }
 }
@@ -0,0 +1,117 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::common_storage::MAX_PERCENTAGE;
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Debug)]
+pub struct FeeTier<M: ManagedTypeApi> {
+    pub min_energy: BigUint<M>,
+    pub fee_percentage: u64,
+}
+
+#[multiversx_sc::module]
+pub trait FeeTiersModule:
+    crate::common::common_storage::CommonStorageModule
+    + crate::events::EventsModule
+    + energy_query::EnergyQueryModule
+{
+    /// The fee percentage applied to users that do not qualify for any fee tier.
+    /// Must be a value between 0 and 10_000, where 10_000 is 100%.
+    #[only_owner]
+    #[endpoint(setFeePercentage)]
+    fn set_fee_percentage(&self, fee_percentage: u64) {
+        self.require_valid_fee_percentage(fee_percentage);
+
+        self.fee_percentage().set(fee_percentage);
+        self.emit_fee_percentage_change_event(fee_percentage);
+    }
+
+    /// Pairs of (min_energy, fee_percentage), sorted by min_energy in ascending order.
+    /// Users with at least min_energy pay the tier's fee percentage.
+    /// Replaces the previous tiers. Passing no tiers removes all of them.
+    #[only_owner]
+    #[endpoint(setFeeTiers)]
+    fn set_fee_tiers(&self, tiers: MultiValueEncoded<MultiValue2<BigUint, u64>>) {
+        let mut fee_tiers = ManagedVec::<Self::Api, FeeTier<Self::Api>>::new();
+        for tier in tiers {
+            let (min_energy, fee_percentage) = tier.into_tuple();
+            self.require_valid_fee_percentage(fee_percentage);
+
+            if !fee_tiers.is_empty() {
+                let last_tier = fee_tiers.get(fee_tiers.len() - 1);
+                require!(min_energy > last_tier.min_energy, "Fee tiers not sorted");
+            }
+
+            fee_tiers.push(FeeTier {
+                min_energy,
+                fee_percentage,
+            });
+        }
+
+        self.fee_tiers().set(&fee_tiers);
+        self.emit_fee_tiers_change_event(&fee_tiers);
+    }
+
+    #[only_owner]
+    #[endpoint(setUserFeeOverride)]
+    fn set_user_fee_override(&self, user: ManagedAddress, fee_percentage: u64) {
+        self.require_valid_fee_percentage(fee_percentage);
+
+        self.user_fee_override(&user).set(fee_percentage);
+        self.emit_user_fee_override_change_event(&user, Some(fee_percentage));
+    }
+
+    #[only_owner]
+    #[endpoint(removeUserFeeOverride)]
+    fn remove_user_fee_override(&self, user: ManagedAddress) {
+        self.user_fee_override(&user).clear();
+        self.emit_user_fee_override_change_event(&user, None);
+    }
+
+    #[view(getEffectiveFeePercentage)]
+    fn get_effective_fee_percentage_view(&self, user: ManagedAddress) -> u64 {
+        self.get_effective_fee_percentage(&user)
+    }
+
+    /// A user fee override takes priority over the fee tiers
+    fn get_effective_fee_percentage(&self, user: &ManagedAddress) -> u64 {
+        let fee_override_mapper = self.user_fee_override(user);
+        if !fee_override_mapper.is_empty() {
+            return fee_override_mapper.get();
+        }
+
+        let fee_tiers = self.fee_tiers().get();
+        if fee_tiers.is_empty() {
+            return self.fee_percentage().get();
+        }
+
+        let user_energy = self.get_energy_amount(user);
+        let mut opt_fee_percentage = None;
+        for tier in fee_tiers.iter() {
+            if user_energy < tier.min_energy {
+                break;
+            }
+
+            opt_fee_percentage = Some(tier.fee_percentage);
+        }
+
+        opt_fee_percentage.unwrap_or_else(|| self.fee_percentage().get())
+    }
+
+    fn require_valid_fee_percentage(&self, fee_percentage: u64) {
+        require!(fee_percentage < MAX_PERCENTAGE, "Invalid fees percentage");
+    }
+
+    #[view(getFeePercentage)]
+    #[storage_mapper("feePercentage")]
+    fn fee_percentage(&self) -> SingleValueMapper<u64>;
+
+    #[view(getFeeTiers)]
+    #[storage_mapper("feeTiers")]
+    fn fee_tiers(&self) -> SingleValueMapper<ManagedVec<FeeTier<Self::Api>>>;
+
+    #[view(getUserFeeOverride)]
+    #[storage_mapper("userFeeOverride")]
+    fn user_fee_override(&self, user: &ManagedAddress) -> SingleValueMapper<u64>;
+}
@@ -11,6 +11,10 @@ multiversx_sc::imports!();
 pub trait FeesModule:
     crate::common::common_storage::CommonStorageModule
     + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + crate::fee_tiers::FeeTiersModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + crate::stats::StatsModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
     + energy_query::EnergyQueryModule
@@ -18,7 +22,7 @@ pub trait FeesModule:
 {
     #[endpoint(claimFees)]
     fn claim_fees(&self) -> PaymentsVec<Self::Api> {
-        self.require_caller_proxy_claim_address();
+        self.require_caller_treasury();
 
         let caller = self.blockchain().get_caller();
         let accumulated_fees_mapper = self.accumulated_fees();
@@ -47,27 +51,139 @@ pub trait FeesModule:
         output_payments
     }
 
+    /// Fee shares are a percentage of the taken fees, where 10_000 is 100%.
+    /// The keeper that processes the user receives keeper_share,
+    /// the user's referrer, if any, receives referrer_share,
+    /// and the treasury receives the rest.
+    #[only_owner]
+    #[endpoint(setFeeShares)]
+    fn set_fee_shares(&self, keeper_share: u64, referrer_share: u64) {
+        require!(
+            keeper_share + referrer_share <= MAX_PERCENTAGE,
+            "Invalid fee shares"
+        );
+
+        self.keeper_fee_share().set(keeper_share);
+        self.referrer_fee_share().set(referrer_share);
+    }
+
+    /// The referrer can only be set once, and is kept until the user unregisters
+    #[endpoint(setReferrer)]
+    fn set_referrer(&self, referrer: ManagedAddress) {
+        self.require_not_withdraw_only();
+
+        let caller = self.blockchain().get_caller();
+        require!(!referrer.is_zero(), "Invalid referrer");
+        require!(caller != referrer, "Cannot refer yourself");
+
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let referrer_mapper = self.user_referrer(user_id);
+        require!(referrer_mapper.is_empty(), "Referrer already set");
+
+        referrer_mapper.set(referrer);
+    }
+
+    #[endpoint(claimKeeperFees)]
+    fn claim_keeper_fees(&self) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let keeper_fees_mapper = self.keeper_fees(&caller);
+        self.claim_common(caller, keeper_fees_mapper)
+    }
+
+    #[endpoint(claimReferrerFees)]
+    fn claim_referrer_fees(&self) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let referrer_fees_mapper = self.referrer_fees(&caller);
+        self.claim_common(caller, referrer_fees_mapper)
+    }
+
+    #[view(getKeeperFees)]
+    fn get_keeper_fees_view(&self, keeper: ManagedAddress) -> MergedRewardsWrapper<Self::Api> {
+        let keeper_fees_mapper = self.keeper_fees(&keeper);
+        if !keeper_fees_mapper.is_empty() {
+            keeper_fees_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        }
+    }
+
+    #[view(getReferrerFees)]
+    fn get_referrer_fees_view(&self, referrer: ManagedAddress) -> MergedRewardsWrapper<Self::Api> {
+        let referrer_fees_mapper = self.referrer_fees(&referrer);
+        if !referrer_fees_mapper.is_empty() {
+            referrer_fees_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        }
+    }
+
+    #[view(getPendingKeeperCompensation)]
+    fn get_pending_keeper_compensation_view(
+        &self,
+        keeper: ManagedAddress,
+    ) -> PaymentsVec<Self::Api> {
+        self.get_pending_keeper_compensation(&keeper).into_payments()
+    }
+
+    fn get_pending_keeper_compensation(
+        &self,
+        keeper: &ManagedAddress,
+    ) -> UniquePayments<Self::Api> {
+        let pending_mapper = self.pending_keeper_compensation(keeper);
+        if !pending_mapper.is_empty() {
+            pending_mapper.get()
+        } else {
+            UniquePayments::new()
+        }
+    }
+
+    #[view(getUserReferrer)]
+    fn get_user_referrer_view(&self, user: ManagedAddress) -> OptionalValue<ManagedAddress> {
+        self.get_user_referrer(&user).into()
+    }
+
+    fn get_user_referrer(&self, user: &ManagedAddress) -> Option<ManagedAddress> {
+        let user_id = self.user_ids().get_id(user);
+        if user_id == NULL_ID {
+            return None;
+        }
+
+        let referrer_mapper = self.user_referrer(user_id);
+        if referrer_mapper.is_empty() {
+            return None;
+        }
+
+        Some(referrer_mapper.get())
+    }
+
+    /// Fees are only taken while processing users, so the caller is always a keeper
     fn take_fees(
         &self,
         user: ManagedAddress,
         rewards_wrapper: &mut MergedRewardsWrapper<Self::Api>,
     ) {
-        let accumulated_fees_mapper = self.accumulated_fees();
-        let mut fees_wrapper = if !accumulated_fees_mapper.is_empty() {
-            accumulated_fees_mapper.get()
-        } else {
-            MergedRewardsWrapper::default()
-        };
+        let fee_percentage = self.get_effective_fee_percentage(&user);
+        let mut fee_tokens = PaymentsVec::new();
 
-        let fee_percentage = self.fee_percentage().get();
-        self.add_locked_token_fees(user, &mut fees_wrapper, rewards_wrapper, fee_percentage);
+        let opt_new_locked_tokens = rewards_wrapper.opt_locked_tokens.as_mut();
+        if let Some(new_locked_tokens) = opt_new_locked_tokens {
+            let locked_fee_tokens = self.deduct_single_fee(new_locked_tokens, fee_percentage);
+            if new_locked_tokens.amount == 0 {
+                rewards_wrapper.opt_locked_tokens = None;
+            }
+            if locked_fee_tokens.amount > 0 {
+                fee_tokens.push(locked_fee_tokens);
+            }
+        }
 
         let other_tokens = rewards_wrapper.other_tokens.clone().into_payments();
         let mut remaining_user_tokens = PaymentsVec::new();
         for i in 0..other_tokens.len() {
             let mut current_token = other_tokens.get(i).clone();
-            let fee_tokens = self.deduct_single_fee(&mut current_token, fee_percentage);
-            fees_wrapper.other_tokens.add_payment(fee_tokens);
+            let current_fee_tokens = self.deduct_single_fee(&mut current_token, fee_percentage);
+            if current_fee_tokens.amount > 0 {
+                fee_tokens.push(current_fee_tokens);
+            }
 
             if current_token.amount > 0 {
                 remaining_user_tokens.push(current_token);
@@ -76,36 +192,133 @@ pub trait FeesModule:
 
         rewards_wrapper.other_tokens =
             UniquePayments::new_from_unique_payments(remaining_user_tokens);
-        accumulated_fees_mapper.set(fees_wrapper);
+
+        self.record_fees_taken(&user, &fee_tokens);
+        self.distribute_fees(user, fee_tokens);
     }
 
-    fn add_locked_token_fees(
+    fn distribute_fees(&self, user: ManagedAddress, fee_tokens: PaymentsVec<Self::Api>) {
+        if fee_tokens.is_empty() {
+            return;
+        }
+
+        let keeper = self.blockchain().get_caller();
+        let opt_referrer = self.get_user_referrer(&user);
+        let keeper_share = self.keeper_fee_share().get();
+        let referrer_share = if opt_referrer.is_some() {
+            self.referrer_fee_share().get()
+        } else {
+            0
+        };
+
+        let locked_token_id = self.get_locked_token_id();
+        let compensation_share = self.keeper_compensation_share().get();
+        let mut treasury_fees = PaymentsVec::new();
+        let mut keeper_fees = PaymentsVec::new();
+        let mut referrer_fees = PaymentsVec::new();
+        let mut compensation_fees = PaymentsVec::new();
+        for fee in fee_tokens.iter() {
+            let keeper_amount = &fee.amount * keeper_share / MAX_PERCENTAGE;
+            let referrer_amount = &fee.amount * referrer_share / MAX_PERCENTAGE;
+            let mut treasury_amount = &fee.amount - &keeper_amount - &referrer_amount;
+            if compensation_share > 0
+                && fee.token_identifier != locked_token_id
+                && !self
+                    .keeper_compensation_pair(&fee.token_identifier)
+                    .is_empty()
+            {
+                let compensation_amount = &treasury_amount * compensation_share / MAX_PERCENTAGE;
+                treasury_amount -= &compensation_amount;
+                self.push_fee_share(&mut compensation_fees, &fee, compensation_amount);
+            }
+
+            self.push_fee_share(&mut treasury_fees, &fee, treasury_amount);
+            self.push_fee_share(&mut keeper_fees, &fee, keeper_amount);
+            self.push_fee_share(&mut referrer_fees, &fee, referrer_amount);
+        }
+
+        if !compensation_fees.is_empty() {
+            let mut pending_compensation = self.get_pending_keeper_compensation(&keeper);
+            for fee in compensation_fees.iter() {
+                pending_compensation.add_payment(fee.clone());
+            }
+            self.pending_keeper_compensation(&keeper).set(pending_compensation);
+        }
+
+        let treasury_address = self.treasury_address().get();
+        self.add_fees(
+            &user,
+            &locked_token_id,
+            treasury_address,
+            self.accumulated_fees(),
+            treasury_fees,
+        );
+        self.add_fees(
+            &user,
+            &locked_token_id,
+            keeper.clone(),
+            self.keeper_fees(&keeper),
+            keeper_fees,
+        );
+        if let Some(referrer) = opt_referrer {
+            self.add_fees(
+                &user,
+                &locked_token_id,
+                referrer.clone(),
+                self.referrer_fees(&referrer),
+                referrer_fees,
+            );
+        }
+    }
+
+    fn push_fee_share(
         &self,
-        user: ManagedAddress,
-        fees_wrapper: &mut MergedRewardsWrapper<Self::Api>,
-        rewards_wrapper: &mut MergedRewardsWrapper<Self::Api>,
-        fee_percentage: u64,
+        fee_shares: &mut PaymentsVec<Self::Api>,
+        fee: &EsdtTokenPayment,
+        share_amount: BigUint,
     ) {
-        let opt_new_locked_tokens = rewards_wrapper.opt_locked_tokens.as_mut();
-        if opt_new_locked_tokens.is_none() {
-            return;
+        if share_amount > 0 {
+            fee_shares.push(EsdtTokenPayment::new(
+                fee.token_identifier.clone(),
+                fee.token_nonce,
+                share_amount,
+            ));
         }
+    }
 
-        let new_locked_tokens = unsafe { opt_new_locked_tokens.unwrap_unchecked() };
-        let fee_tokens = self.deduct_single_fee(new_locked_tokens, fee_percentage);
-        if fee_tokens.amount == 0 {
+    /// The energy of the locked token fees is moved from the user to the fees recipient
+    fn add_fees(
+        &self,
+        user: &ManagedAddress,
+        locked_token_id: &TokenIdentifier,
+        recipient: ManagedAddress,
+        fees_mapper: SingleValueMapper<MergedRewardsWrapper<Self::Api>>,
+        fees: PaymentsVec<Self::Api>,
+    ) {
+        if fees.is_empty() {
             return;
         }
-        if new_locked_tokens.amount == 0 {
-            rewards_wrapper.opt_locked_tokens = None;
-        }
 
-        let proxy_addr = self.proxy_claim_address().get();
-        let fee_tokens_vec = ManagedVec::from_single_item(fee_tokens.clone());
-        self.deduct_energy_from_sender(user, &fee_tokens_vec);
-        self.add_energy_to_destination(proxy_addr.clone(), &fee_tokens_vec);
+        let mut fees_wrapper = if !fees_mapper.is_empty() {
+            fees_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        };
+
+        for fee in fees.iter() {
+            if &fee.token_identifier != locked_token_id {
+                fees_wrapper.other_tokens.add_payment(fee.clone());
+                continue;
+            }
+
+            let fee_tokens_vec = ManagedVec::from_single_item(fee.clone());
+            self.deduct_energy_from_sender(user.clone(), &fee_tokens_vec);
+            self.add_energy_to_destination(recipient.clone(), &fee_tokens_vec);
 
-        self.merge_wrapped_locked_tokens(proxy_addr, fees_wrapper, fee_tokens);
+            self.merge_wrapped_locked_tokens(recipient.clone(), &mut fees_wrapper, fee.clone());
+        }
+
+        fees_mapper.set(fees_wrapper);
     }
 
     fn deduct_single_fee(
@@ -127,11 +340,48 @@ pub trait FeesModule:
         payment_amount * fee_percentage / MAX_PERCENTAGE
     }
 
-    #[view(getFeePercentage)]
-    #[storage_mapper("feePercentage")]
-    fn fee_percentage(&self) -> SingleValueMapper<u64>;
-
     #[view(getAccumulatedFees)]
     #[storage_mapper("accumulatedFees")]
     fn accumulated_fees(&self) -> SingleValueMapper<MergedRewardsWrapper<Self::Api>>;
+
+    #[storage_mapper("keeperFees")]
+    fn keeper_fees(
+        &self,
+        keeper: &ManagedAddress,
+    ) -> SingleValueMapper<MergedRewardsWrapper<Self::Api>>;
+
+    #[storage_mapper("referrerFees")]
+    fn referrer_fees(
+        &self,
+        referrer: &ManagedAddress,
+    ) -> SingleValueMapper<MergedRewardsWrapper<Self::Api>>;
+
+    #[view(getKeeperFeeShare)]
+    #[storage_mapper("keeperFeeShare")]
+    fn keeper_fee_share(&self) -> SingleValueMapper<u64>;
+
+    #[view(getReferrerFeeShare)]
+    #[storage_mapper("referrerFeeShare")]
+    fn referrer_fee_share(&self) -> SingleValueMapper<u64>;
+
+    /// Percentage of the treasury's non-locked fees that is converted to EGLD for the keepers
+    #[view(getKeeperCompensationShare)]
+    #[storage_mapper("keeperCompensationShare")]
+    fn keeper_compensation_share(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("keeperCompensationPair")]
+    fn keeper_compensation_pair(
+        &self,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<ManagedAddress>;
+
+    /// Fees taken by the keeper's batches, waiting to be converted at the end of its next batch
+    #[storage_mapper("pendingKeeperCompensation")]
+    fn pending_keeper_compensation(
+        &self,
+        keeper: &ManagedAddress,
+    ) -> SingleValueMapper<UniquePayments<Self::Api>>;
+
+    #[storage_mapper("userReferrer")]
+    fn user_referrer(&self, user_id: AddressId) -> SingleValueMapper<ManagedAddress>;
 }
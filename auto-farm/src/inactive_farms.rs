@@ -0,0 +1,101 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use read_external_storage::State;
+
+/// Skip must stay the first variant, as it is the default policy
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, Debug)]
+pub enum InactiveFarmPolicy {
+    Skip,
+    Notify,
+    Exit,
+}
+
+#[multiversx_sc::module]
+pub trait InactiveFarmsModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::user_tokens::user_delegates::UserDelegatesModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + utils::UtilsModule
+{
+    /// Sets how user positions are handled while the farm is not active:
+    ///     Skip - positions are kept as they are
+    ///     Notify - positions are kept, and an event is emitted for each of them
+    ///     Exit - positions are exited, and the farming tokens are added to the user's rewards
+    ///
+    /// Exit is only supported for farms with an exitFarm endpoint, so not for farm-staking contracts.
+    #[only_owner]
+    #[endpoint(setInactiveFarmPolicy)]
+    fn set_inactive_farm_policy(&self, farm_address: ManagedAddress, policy: InactiveFarmPolicy) {
+        let farm_id = self.farm_ids().get_id_non_zero(&farm_address);
+        if policy == InactiveFarmPolicy::Exit {
+            let is_staking_farm = self
+                .get_farm_pair_contract_address_mapper(farm_address)
+                .is_empty();
+            require!(
+                !is_staking_farm,
+                "Exit policy not supported for staking farms"
+            );
+        }
+
+        self.inactive_farm_policy(farm_id).set(policy);
+    }
+
+    #[view(getInactiveFarmPolicy)]
+    fn get_inactive_farm_policy_view(&self, farm_address: ManagedAddress) -> InactiveFarmPolicy {
+        let farm_id = self.farm_ids().get_id_non_zero(&farm_address);
+        self.inactive_farm_policy(farm_id).get()
+    }
+
+    /// Returns the users that have at least one position in a whitelisted farm that is not active.
+    /// Only the user IDs in [start_id, start_id + count) are checked.
+    #[view(getUsersInInactiveFarms)]
+    fn get_users_in_inactive_farms(
+        &self,
+        start_id: AddressId,
+        count: usize,
+    ) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+        let ids_mapper = self.user_ids();
+        let farms_mapper = self.farm_ids();
+        let start_id = core::cmp::max(start_id, 1);
+        let end_id = core::cmp::min(
+            start_id.saturating_add(count as AddressId),
+            ids_mapper.get_last_id() + 1,
+        );
+        for user_id in start_id..end_id {
+            let opt_user = ids_mapper.get_address(user_id);
+            if opt_user.is_none() {
+                continue;
+            }
+
+            let user = unsafe { opt_user.unwrap_unchecked() };
+            let user_farm_tokens = self.get_all_user_farm_tokens(user_id);
+            for farm_token in user_farm_tokens.iter() {
+                let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
+                let opt_farm_addr = farms_mapper.get_address(farm_id);
+                if opt_farm_addr.is_none() {
+                    continue;
+                }
+
+                let farm_addr = unsafe { opt_farm_addr.unwrap_unchecked() };
+                if self.get_farm_state(farm_addr) != State::Active {
+                    result.push(user);
+                    break;
+                }
+            }
+        }
+
+        result
+    }
+
+    #[storage_mapper("inactiveFarmPolicy")]
+    fn inactive_farm_policy(&self, farm_id: AddressId) -> SingleValueMapper<InactiveFarmPolicy>;
+}
@@ -3,42 +3,81 @@
 multiversx_sc::imports!();
 
 pub mod common;
+pub mod contract_state;
+pub mod deposit_caps;
 pub mod events;
 pub mod external_sc_interactions;
 pub mod external_storage_read;
+pub mod fee_tiers;
 pub mod fees;
+pub mod inactive_farms;
+pub mod portfolio;
 pub mod registration;
+pub mod stats;
 pub mod user_tokens;
 pub mod whitelists;
 
 use common::common_storage::MAX_PERCENTAGE;
+use permissions_module::Permissions;
 
 #[multiversx_sc::contract]
 pub trait AutoFarm:
     read_external_storage::ReadExternalStorageModule
     + whitelists::farms_whitelist::FarmsWhitelistModule
+    + whitelists::keepers_whitelist::KeepersWhitelistModule
     + external_storage_read::farm_storage_read::FarmStorageReadModule
     + common::common_storage::CommonStorageModule
     + registration::RegistrationModule
     + user_tokens::user_farm_tokens::UserFarmTokensModule
     + external_sc_interactions::farm_actions::FarmActionsModule
+    + external_sc_interactions::farm_migration::FarmMigrationModule
     + external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule
     + external_sc_interactions::locked_token_merging::LockedTokenMergingModule
     + whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + user_tokens::user_locked_tokens::UserLockedTokensModule
+    + whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule
+    + user_tokens::user_wrapped_farm_tokens::UserWrappedFarmTokensModule
+    + external_sc_interactions::proxy_dex_actions::ProxyDexActionsModule
     + external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
     + external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule
+    + external_sc_interactions::metastaking_compounding::MetastakingCompoundingModule
+    + external_sc_interactions::keeper_compensation::KeeperCompensationModule
+    + external_sc_interactions::energy_compounding::EnergyCompoundingModule
+    + external_sc_interactions::energy_update::EnergyUpdateModule
     + external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule
+    + external_sc_interactions::users_processing::UsersProcessingModule
+    + external_sc_interactions::position_creation::PositionCreationModule
     + user_tokens::user_rewards::UserRewardsModule
+    + user_tokens::user_payouts::UserPayoutsModule
+    + user_tokens::user_strategy::UserStrategyModule
+    + user_tokens::user_delegates::UserDelegatesModule
+    + user_tokens::position_transfer::PositionTransferModule
     + user_tokens::withdraw_tokens::WithdrawTokensModule
+    + fee_tiers::FeeTiersModule
     + fees::FeesModule
+    + deposit_caps::DepositCapsModule
+    + inactive_farms::InactiveFarmsModule
+    + portfolio::PortfolioModule
+    + stats::StatsModule
     + events::EventsModule
+    + contract_state::ContractStateModule
+    + permissions_module::PermissionsModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
     + energy_query::EnergyQueryModule
     + utils::UtilsModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+    + auto_pos_creator::external_sc_interactions::pair_actions::PairActionsModule
+    + auto_pos_creator::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + auto_pos_creator::external_sc_interactions::router_actions::RouterActionsModule
+    + auto_pos_creator::external_sc_interactions::farm_actions::FarmActionsModule
+    + auto_pos_creator::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + auto_pos_creator::multi_contract_interactions::create_pos::CreatePosModule
 {
-    /// proxy_claim_address: The address that can call the claim endpoints for users
+    /// proxy_claim_address: The first keeper, which can call the claim endpoints for users.
+    ///     It is also set as the treasury address, which receives the fees.
     /// fee_percentage: The percentage of rewards that are taken as fees for every action.
     ///     Must be a value between 0 and 10_000, where 10_000 is 100%.
     /// energy_factory_address: SC address handling user energy
@@ -57,7 +96,11 @@ pub trait AutoFarm:
         self.require_sc_address(&energy_factory_address);
         self.require_sc_address(&fees_collector_sc_address);
 
-        self.proxy_claim_address().set_if_empty(proxy_claim_address);
+        let caller = self.blockchain().get_caller();
+        self.add_permissions(caller, Permissions::OWNER);
+
+        let _ = self.keepers().insert(proxy_claim_address.clone());
+        self.treasury_address().set_if_empty(proxy_claim_address);
         self.fee_percentage().set(fee_percentage);
         self.energy_factory_address()
             .set_if_empty(energy_factory_address);
@@ -65,18 +108,39 @@ pub trait AutoFarm:
             .set_if_empty(fees_collector_sc_address);
     }
 
+    /// The previous proxy claim address, if any, becomes both a keeper and the treasury
     #[upgrade]
-    fn upgrade(&self) {}
+    fn upgrade(&self) {
+        let owner = self.blockchain().get_owner_address();
+        self.add_permissions(owner, Permissions::OWNER);
+
+        let proxy_claim_address_mapper = self.proxy_claim_address();
+        if proxy_claim_address_mapper.is_empty() {
+            return;
+        }
+
+        let proxy_claim_address = proxy_claim_address_mapper.take();
+        let _ = self.keepers().insert(proxy_claim_address.clone());
+        self.treasury_address().set_if_empty(proxy_claim_address);
+    }
 
     #[only_owner]
-    #[endpoint(changeProxyClaimAddress)]
-    fn change_proxy_claim_address(&self, new_proxy_claim_address: ManagedAddress) {
-        let old_claim_address = self.proxy_claim_address().replace(&new_proxy_claim_address);
-        let unclaimed_tokens = self.accumulated_fees().get();
+    #[endpoint(changeTreasuryAddress)]
+    fn change_treasury_address(&self, new_treasury_address: ManagedAddress) {
+        let old_treasury_address = self.treasury_address().replace(&new_treasury_address);
+        let accumulated_fees_mapper = self.accumulated_fees();
+        if accumulated_fees_mapper.is_empty() {
+            return;
+        }
+
+        let unclaimed_tokens = accumulated_fees_mapper.get();
         if let Some(locked_tokens) = unclaimed_tokens.opt_locked_tokens {
             let tokens_vec = ManagedVec::from_single_item(locked_tokens);
-            self.deduct_energy_from_sender(old_claim_address, &tokens_vec);
-            self.add_energy_to_destination(new_proxy_claim_address, &tokens_vec);
+            self.deduct_energy_from_sender(old_treasury_address, &tokens_vec);
+            self.add_energy_to_destination(new_treasury_address, &tokens_vec);
         }
     }
+
+    #[storage_mapper("proxyClaimAddress")]
+    fn proxy_claim_address(&self) -> SingleValueMapper<ManagedAddress>;
 }
@@ -0,0 +1,318 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::{FarmTokenAttributes, PaymentsVec};
+use farm_staking::token_attributes::StakingFarmTokenAttributes;
+use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
+
+use crate::common::rewards_wrapper::MergedRewardsWrapper;
+
+#[type_abi]
+#[derive(TopEncode, NestedEncode, ManagedVecItem, Debug)]
+pub struct PositionInfo<M: ManagedTypeApi> {
+    pub position: EsdtTokenPayment<M>,
+    pub sc_address: ManagedAddress<M>,
+    pub lp_tokens: EsdtTokenPayment<M>,
+    pub first_tokens: EsdtTokenPayment<M>,
+    pub second_tokens: EsdtTokenPayment<M>,
+    pub pending_rewards: PaymentsVec<M>,
+    pub estimated_fees: PaymentsVec<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, Debug)]
+pub struct UserPortfolio<M: ManagedTypeApi> {
+    pub farm_positions: ManagedVec<M, PositionInfo<M>>,
+    pub metastaking_positions: ManagedVec<M, PositionInfo<M>>,
+    pub unclaimed_rewards: MergedRewardsWrapper<M>,
+    pub fee_percentage: u64,
+}
+
+#[multiversx_sc::module]
+pub trait PortfolioModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::fee_tiers::FeeTiersModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+    + auto_pos_creator::configs::pairs_config::PairsConfigModule
+{
+    /// Returns the user's farm and metastaking positions, each with the underlying
+    /// LP and pair tokens, and an estimate of the rewards accumulated since the last claim.
+    /// Pending rewards only include base rewards, and are expressed in the farm's reward token.
+    ///
+    /// estimated_fees are the fees that would be taken from the pending rewards.
+    /// The unclaimed rewards held in auto-farm already had the fees taken.
+    ///
+    /// Deposited locked tokens and wrapped farm tokens are not included.
+    /// They are returned by getUserLockedTokens and getUserWrappedFarmTokens.
+    #[view(getUserPortfolio)]
+    fn get_user_portfolio(&self, user: ManagedAddress) -> UserPortfolio<Self::Api> {
+        let mut portfolio = UserPortfolio {
+            farm_positions: ManagedVec::new(),
+            metastaking_positions: ManagedVec::new(),
+            unclaimed_rewards: MergedRewardsWrapper::default(),
+            fee_percentage: self.get_effective_fee_percentage(&user),
+        };
+
+        let user_id = self.user_ids().get_id(&user);
+        if user_id == NULL_ID {
+            return portfolio;
+        }
+
+        let farms_mapper = self.farm_ids();
+        for farm_token in self.get_all_user_farm_tokens(user_id).iter() {
+            let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
+            let opt_farm_address = farms_mapper.get_address(farm_id);
+            if let Some(farm_address) = opt_farm_address {
+                let position_info = self.get_farm_position_info(
+                    &farm_token,
+                    farm_address,
+                    portfolio.fee_percentage,
+                );
+                portfolio.farm_positions.push(position_info);
+            }
+        }
+
+        let ms_mapper = self.metastaking_ids();
+        for dual_yield_token in self.get_all_user_metastaking_tokens(user_id).iter() {
+            let ms_id = self
+                .metastaking_for_dual_yield_token(&dual_yield_token.token_identifier)
+                .get();
+            let opt_ms_address = ms_mapper.get_address(ms_id);
+            if let Some(ms_address) = opt_ms_address {
+                let position_info = self.get_metastaking_position_info(
+                    &dual_yield_token,
+                    ms_address,
+                    portfolio.fee_percentage,
+                );
+                portfolio.metastaking_positions.push(position_info);
+            }
+        }
+
+        let user_rewards_mapper = self.user_rewards(user_id);
+        if !user_rewards_mapper.is_empty() {
+            portfolio.unclaimed_rewards = user_rewards_mapper.get();
+        }
+
+        portfolio
+    }
+
+    fn get_farm_position_info(
+        &self,
+        farm_token: &EsdtTokenPayment,
+        farm_address: ManagedAddress,
+        fee_percentage: u64,
+    ) -> PositionInfo<Self::Api> {
+        let is_staking_farm = self
+            .get_farm_pair_contract_address_mapper(farm_address.clone())
+            .is_empty();
+        if is_staking_farm {
+            return self.get_staking_farm_position_info(farm_token, farm_address, fee_percentage);
+        }
+
+        let attributes: FarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_token_attributes(&farm_token.token_identifier, farm_token.token_nonce);
+        let pending_rewards = self.estimate_farm_position_rewards(
+            farm_address.clone(),
+            &attributes.reward_per_share,
+            &farm_token.amount,
+        );
+
+        let mut position_info = self.new_position_info(
+            farm_token,
+            farm_address.clone(),
+            &farm_address,
+            &farm_token.amount,
+        );
+        self.add_pending_rewards(&mut position_info, pending_rewards, fee_percentage);
+
+        position_info
+    }
+
+    /// Staking farms have no pair, so the staked tokens are returned as first_tokens,
+    /// while lp_tokens and second_tokens are empty
+    fn get_staking_farm_position_info(
+        &self,
+        farm_token: &EsdtTokenPayment,
+        farm_address: ManagedAddress,
+        fee_percentage: u64,
+    ) -> PositionInfo<Self::Api> {
+        let attributes: StakingFarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_token_attributes(&farm_token.token_identifier, farm_token.token_nonce);
+        let pending_rewards = self.estimate_farm_position_rewards(
+            farm_address.clone(),
+            &attributes.reward_per_share,
+            &farm_token.amount,
+        );
+
+        let staking_token_id = self.get_farming_token_id_mapper(farm_address.clone()).get();
+        let empty_tokens = EsdtTokenPayment::new(
+            TokenIdentifier::from(ManagedBuffer::new()),
+            0,
+            BigUint::zero(),
+        );
+        let mut position_info = PositionInfo {
+            position: farm_token.clone(),
+            sc_address: farm_address,
+            lp_tokens: empty_tokens.clone(),
+            first_tokens: EsdtTokenPayment::new(staking_token_id, 0, farm_token.amount.clone()),
+            second_tokens: empty_tokens,
+            pending_rewards: PaymentsVec::new(),
+            estimated_fees: PaymentsVec::new(),
+        };
+        self.add_pending_rewards(&mut position_info, pending_rewards, fee_percentage);
+
+        position_info
+    }
+
+    /// The dual yield token attributes hold the amounts of the whole token nonce,
+    /// so they are scaled down to the user's share of it
+    fn get_metastaking_position_info(
+        &self,
+        dual_yield_token: &EsdtTokenPayment,
+        ms_address: ManagedAddress,
+        fee_percentage: u64,
+    ) -> PositionInfo<Self::Api> {
+        let dual_yield_attributes: DualYieldTokenAttributes<Self::Api> =
+            self.blockchain().get_token_attributes(
+                &dual_yield_token.token_identifier,
+                dual_yield_token.token_nonce,
+            );
+        let (lp_farm_token_amount, staking_token_amount) =
+            if dual_yield_attributes.virtual_pos_token_amount > 0 {
+                let lp_farm_token_amount = &dual_yield_attributes.lp_farm_token_amount
+                    * &dual_yield_token.amount
+                    / &dual_yield_attributes.virtual_pos_token_amount;
+
+                (lp_farm_token_amount, dual_yield_token.amount.clone())
+            } else {
+                (BigUint::zero(), BigUint::zero())
+            };
+        let lp_farm_address = self.get_lp_farm_address_mapper(ms_address.clone()).get();
+        let staking_farm_address = self
+            .get_staking_farm_address_mapper(ms_address.clone())
+            .get();
+
+        let mut position_info = self.new_position_info(
+            dual_yield_token,
+            ms_address.clone(),
+            &lp_farm_address,
+            &lp_farm_token_amount,
+        );
+
+        let lp_farm_token_id = self.get_lp_farm_token_id_mapper(ms_address.clone()).get();
+        let lp_farm_attributes: FarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(
+                &ms_address,
+                &lp_farm_token_id,
+                dual_yield_attributes.lp_farm_token_nonce,
+            )
+            .decode_attributes();
+        let lp_farm_rewards = self.estimate_farm_position_rewards(
+            lp_farm_address,
+            &lp_farm_attributes.reward_per_share,
+            &lp_farm_token_amount,
+        );
+        self.add_pending_rewards(&mut position_info, lp_farm_rewards, fee_percentage);
+
+        let staking_token_id = self
+            .get_farm_token_id_mapper(staking_farm_address.clone())
+            .get();
+        let staking_attributes: StakingFarmTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_esdt_token_data(
+                &ms_address,
+                &staking_token_id,
+                dual_yield_attributes.virtual_pos_token_nonce,
+            )
+            .decode_attributes();
+        let staking_rewards = self.estimate_farm_position_rewards(
+            staking_farm_address,
+            &staking_attributes.reward_per_share,
+            &staking_token_amount,
+        );
+        self.add_pending_rewards(&mut position_info, staking_rewards, fee_percentage);
+
+        position_info
+    }
+
+    fn new_position_info(
+        &self,
+        position: &EsdtTokenPayment,
+        sc_address: ManagedAddress,
+        lp_farm_address: &ManagedAddress,
+        lp_amount: &BigUint,
+    ) -> PositionInfo<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(lp_farm_address.clone())
+            .get();
+        let pair_config = self.get_pair_config(&pair_address);
+        let lp_supply = self.get_lp_token_supply_mapper(pair_address.clone()).get();
+
+        let (first_amount, second_amount) = if lp_supply > 0 {
+            let first_reserve = self
+                .get_pair_reserve_mapper(pair_address.clone(), &pair_config.first_token_id)
+                .get();
+            let second_reserve = self
+                .get_pair_reserve_mapper(pair_address, &pair_config.second_token_id)
+                .get();
+
+            (
+                lp_amount * &first_reserve / &lp_supply,
+                lp_amount * &second_reserve / &lp_supply,
+            )
+        } else {
+            (BigUint::zero(), BigUint::zero())
+        };
+
+        PositionInfo {
+            position: position.clone(),
+            sc_address,
+            lp_tokens: EsdtTokenPayment::new(pair_config.lp_token_id, 0, lp_amount.clone()),
+            first_tokens: EsdtTokenPayment::new(pair_config.first_token_id, 0, first_amount),
+            second_tokens: EsdtTokenPayment::new(pair_config.second_token_id, 0, second_amount),
+            pending_rewards: PaymentsVec::new(),
+            estimated_fees: PaymentsVec::new(),
+        }
+    }
+
+    fn add_pending_rewards(
+        &self,
+        position_info: &mut PositionInfo<Self::Api>,
+        pending_rewards: EsdtTokenPayment,
+        fee_percentage: u64,
+    ) {
+        if pending_rewards.amount == 0 {
+            return;
+        }
+
+        let fee_amount = self.calculate_fee_amount(&pending_rewards.amount, fee_percentage);
+        if fee_amount > 0 {
+            position_info.estimated_fees.push(EsdtTokenPayment::new(
+                pending_rewards.token_identifier.clone(),
+                0,
+                fee_amount,
+            ));
+        }
+
+        position_info.pending_rewards.push(pending_rewards);
+    }
+}
@@ -9,6 +9,7 @@ pub trait RegistrationModule:
     read_external_storage::ReadExternalStorageModule
     + crate::common::common_storage::CommonStorageModule
     + crate::user_tokens::user_rewards::UserRewardsModule
+    + crate::user_tokens::user_payouts::UserPayoutsModule
     + crate::fees::FeesModule
     + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
     + crate::whitelists::farms_whitelist::FarmsWhitelistModule
@@ -16,8 +17,18 @@ pub trait RegistrationModule:
     + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
     + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::user_tokens::user_locked_tokens::UserLockedTokensModule
+    + crate::whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule
+    + crate::user_tokens::user_wrapped_farm_tokens::UserWrappedFarmTokensModule
     + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::user_tokens::user_strategy::UserStrategyModule
+    + crate::user_tokens::user_delegates::UserDelegatesModule
+    + crate::external_sc_interactions::energy_compounding::EnergyCompoundingModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_migration::FarmMigrationModule
+    + crate::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule
+    + crate::external_sc_interactions::energy_update::EnergyUpdateModule
     + crate::events::EventsModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
@@ -26,6 +37,8 @@ pub trait RegistrationModule:
 {
     #[endpoint]
     fn register(&self) {
+        self.require_not_withdraw_only();
+
         let caller = self.blockchain().get_caller();
         let _ = self.user_ids().insert_new(&caller);
         self.emit_user_register_event(&caller);
@@ -37,13 +50,27 @@ pub trait RegistrationModule:
         let ids_mapper = self.user_ids();
         let user_id = ids_mapper.get_id_non_zero(&caller);
 
-        let farm_tokens = self.withdraw_all_tokens(&caller, &self.user_farm_tokens(user_id));
-        let ms_tokens = self.withdraw_all_tokens(&caller, &self.user_metastaking_tokens(user_id));
+        let farm_tokens = self.withdraw_all_user_farm_tokens(&caller, user_id);
+        let ms_tokens = self.withdraw_all_user_metastaking_tokens(&caller, user_id);
+        let wrapped_farm_tokens =
+            self.withdraw_all_tokens(&caller, &self.user_wrapped_farm_tokens(user_id));
+        let locked_tokens = self.withdraw_all_locked_tokens(&caller, user_id);
         let claimed_rewards = self.user_claim_rewards(caller.clone(), user_id);
+        self.user_strategy(user_id).clear();
+        self.user_energy_compound_lock_epochs(user_id).clear();
+        self.last_energy_compound(user_id).clear();
+        self.user_last_updated_energy(user_id).clear();
+        self.user_referrer(user_id).clear();
+        self.clear_user_payout_settings(user_id);
+        self.user_delegates(user_id).clear();
+        self.clear_user_lifetime_stats(user_id);
+        self.user_migrated_farm_tokens(user_id).clear();
         let _ = ids_mapper.remove_by_id(user_id);
 
         let mut results = farm_tokens;
         results.append_vec(ms_tokens);
+        results.append_vec(wrapped_farm_tokens);
+        results.append_vec(locked_tokens);
         results.append_vec(claimed_rewards);
 
         self.emit_token_withdrawal_event(&caller, WithdrawType::AllTokens, &results);
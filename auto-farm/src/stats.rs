@@ -0,0 +1,110 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+
+/// Cumulative amounts per token. Token nonces are ignored, so all amounts have nonce 0.
+#[type_abi]
+#[derive(TopEncode, TopDecode, PartialEq, Debug)]
+pub struct LifetimeStats<M: ManagedTypeApi> {
+    pub claimed_rewards: PaymentsVec<M>,
+    pub compounded_rewards: PaymentsVec<M>,
+    pub fees_taken: PaymentsVec<M>,
+}
+
+#[multiversx_sc::module]
+pub trait StatsModule: crate::common::common_storage::CommonStorageModule {
+    #[view(getUserLifetimeStats)]
+    fn get_user_lifetime_stats(&self, user: ManagedAddress) -> LifetimeStats<Self::Api> {
+        let user_id = self.user_ids().get_id(&user);
+        LifetimeStats {
+            claimed_rewards: self.get_counter_payments(&self.user_claimed_rewards(user_id)),
+            compounded_rewards: self.get_counter_payments(&self.user_compounded_rewards(user_id)),
+            fees_taken: self.get_counter_payments(&self.user_fees_taken(user_id)),
+        }
+    }
+
+    #[view(getGlobalLifetimeStats)]
+    fn get_global_lifetime_stats(&self) -> LifetimeStats<Self::Api> {
+        LifetimeStats {
+            claimed_rewards: self.get_counter_payments(&self.total_claimed_rewards()),
+            compounded_rewards: self.get_counter_payments(&self.total_compounded_rewards()),
+            fees_taken: self.get_counter_payments(&self.total_fees_taken()),
+        }
+    }
+
+    /// Rewards added to the user's storage, after the fees were taken
+    fn record_claimed_rewards(&self, user_id: AddressId, rewards: &PaymentsVec<Self::Api>) {
+        for payment in rewards.iter() {
+            self.add_to_counter(&mut self.user_claimed_rewards(user_id), &payment);
+            self.add_to_counter(&mut self.total_claimed_rewards(), &payment);
+        }
+    }
+
+    fn record_compounded_rewards(&self, user_id: AddressId, payment: &EsdtTokenPayment) {
+        self.add_to_counter(&mut self.user_compounded_rewards(user_id), payment);
+        self.add_to_counter(&mut self.total_compounded_rewards(), payment);
+    }
+
+    fn record_fees_taken(&self, user: &ManagedAddress, fees: &PaymentsVec<Self::Api>) {
+        let user_id = self.user_ids().get_id(user);
+        for payment in fees.iter() {
+            if user_id != NULL_ID {
+                self.add_to_counter(&mut self.user_fees_taken(user_id), &payment);
+            }
+            self.add_to_counter(&mut self.total_fees_taken(), &payment);
+        }
+    }
+
+    fn add_to_counter(
+        &self,
+        counter_mapper: &mut MapMapper<TokenIdentifier, BigUint>,
+        payment: &EsdtTokenPayment,
+    ) {
+        if payment.amount == 0 {
+            return;
+        }
+
+        let new_amount = match counter_mapper.get(&payment.token_identifier) {
+            Some(amount) => amount + &payment.amount,
+            None => payment.amount.clone(),
+        };
+        let _ = counter_mapper.insert(payment.token_identifier.clone(), new_amount);
+    }
+
+    fn get_counter_payments(
+        &self,
+        counter_mapper: &MapMapper<TokenIdentifier, BigUint>,
+    ) -> PaymentsVec<Self::Api> {
+        let mut payments = PaymentsVec::new();
+        for (token_id, amount) in counter_mapper.iter() {
+            payments.push(EsdtTokenPayment::new(token_id, 0, amount));
+        }
+
+        payments
+    }
+
+    fn clear_user_lifetime_stats(&self, user_id: AddressId) {
+        self.user_claimed_rewards(user_id).clear();
+        self.user_compounded_rewards(user_id).clear();
+        self.user_fees_taken(user_id).clear();
+    }
+
+    #[storage_mapper("userClaimedRewards")]
+    fn user_claimed_rewards(&self, user_id: AddressId) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("userCompoundedRewards")]
+    fn user_compounded_rewards(&self, user_id: AddressId) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("userFeesTaken")]
+    fn user_fees_taken(&self, user_id: AddressId) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("totalClaimedRewards")]
+    fn total_claimed_rewards(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("totalCompoundedRewards")]
+    fn total_compounded_rewards(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("totalFeesTaken")]
+    fn total_fees_taken(&self) -> MapMapper<TokenIdentifier, BigUint>;
+}
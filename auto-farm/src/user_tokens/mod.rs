@@ -1,4 +1,10 @@
+pub mod position_transfer;
+pub mod user_delegates;
 pub mod user_farm_tokens;
+pub mod user_locked_tokens;
 pub mod user_metastaking_tokens;
+pub mod user_payouts;
 pub mod user_rewards;
+pub mod user_strategy;
+pub mod user_wrapped_farm_tokens;
 pub mod withdraw_tokens;
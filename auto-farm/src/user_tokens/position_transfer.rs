@@ -0,0 +1,127 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::common::rewards_wrapper::MergedRewardsWrapper;
+
+#[multiversx_sc::module]
+pub trait PositionTransferModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + super::user_farm_tokens::UserFarmTokensModule
+    + super::user_metastaking_tokens::UserMetastakingTokensModule
+    + super::user_rewards::UserRewardsModule
+    + super::user_delegates::UserDelegatesModule
+    + super::withdraw_tokens::WithdrawTokensModule
+    + crate::fees::FeesModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Moves farm and metastaking positions to another registered user, without withdrawing them.
+    /// Positions are selected by token identifier and nonce. Partial amounts can be transferred.
+    ///
+    /// If transfer_rewards is true, the caller's unclaimed rewards are moved as well,
+    /// together with the energy of the locked tokens.
+    #[endpoint(transferPositions)]
+    fn transfer_positions(
+        &self,
+        to: ManagedAddress,
+        positions: PaymentsVec<Self::Api>,
+        transfer_rewards: bool,
+    ) {
+        self.require_active();
+
+        let caller = self.blockchain().get_caller();
+        require!(caller != to, "Cannot transfer to yourself");
+
+        let ids_mapper = self.user_ids();
+        let sender_id = ids_mapper.get_id_non_zero(&caller);
+        let receiver_id = ids_mapper.get_id_non_zero(&to);
+
+        self.migrate_legacy_user_farm_tokens(sender_id);
+        self.migrate_legacy_user_metastaking_tokens(sender_id);
+        self.migrate_legacy_user_farm_tokens(receiver_id);
+        self.migrate_legacy_user_metastaking_tokens(receiver_id);
+
+        self.transfer_positions_usage(sender_id, receiver_id, &positions);
+
+        for position in positions.iter() {
+            let single_position = PaymentsVec::from_single_item(position.clone());
+            let farm_id = self.farm_for_farm_token(&position.token_identifier).get();
+            if farm_id != NULL_ID {
+                self.remove_user_farm_tokens(sender_id, &single_position);
+                self.add_user_farm_position(receiver_id, farm_id, position.clone());
+                continue;
+            }
+
+            let ms_id = self
+                .metastaking_for_dual_yield_token(&position.token_identifier)
+                .get();
+            require!(ms_id != NULL_ID, "Invalid token");
+
+            self.remove_user_metastaking_tokens(sender_id, &single_position);
+            self.add_user_metastaking_position(receiver_id, ms_id, position.clone());
+        }
+
+        let transferred_rewards = if transfer_rewards {
+            self.transfer_user_rewards(&caller, sender_id, &to, receiver_id)
+        } else {
+            PaymentsVec::new()
+        };
+
+        self.emit_positions_transfer_event(&caller, &to, &positions, &transferred_rewards);
+    }
+
+    /// Returns the transferred rewards
+    fn transfer_user_rewards(
+        &self,
+        from: &ManagedAddress,
+        from_id: AddressId,
+        to: &ManagedAddress,
+        to_id: AddressId,
+    ) -> PaymentsVec<Self::Api> {
+        let sender_rewards_mapper = self.user_rewards(from_id);
+        if sender_rewards_mapper.is_empty() {
+            return PaymentsVec::new();
+        }
+
+        let sender_rewards = sender_rewards_mapper.take();
+        let receiver_rewards_mapper = self.user_rewards(to_id);
+        let mut receiver_rewards = if !receiver_rewards_mapper.is_empty() {
+            receiver_rewards_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        };
+
+        let mut transferred_rewards = sender_rewards.other_tokens.into_payments();
+        for payment in transferred_rewards.iter() {
+            receiver_rewards.other_tokens.add_payment(payment.clone());
+        }
+
+        if let Some(locked_tokens) = sender_rewards.opt_locked_tokens {
+            let locked_tokens_vec = ManagedVec::from_single_item(locked_tokens.clone());
+            self.deduct_energy_from_sender(from.clone(), &locked_tokens_vec);
+            self.add_energy_to_destination(to.clone(), &locked_tokens_vec);
+
+            self.merge_wrapped_locked_tokens(
+                to.clone(),
+                &mut receiver_rewards,
+                locked_tokens.clone(),
+            );
+            transferred_rewards.push(locked_tokens);
+        }
+
+        receiver_rewards_mapper.set(&receiver_rewards);
+
+        transferred_rewards
+    }
+}
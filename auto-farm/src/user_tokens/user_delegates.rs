@@ -0,0 +1,118 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::Epoch;
+
+pub const CHANGE_STRATEGY_RIGHT: u32 = 1 << 0;
+pub const CLAIM_REWARDS_RIGHT: u32 = 1 << 1;
+pub const WITHDRAW_RIGHT: u32 = 1 << 2;
+pub const ALL_DELEGATE_RIGHTS: u32 = CHANGE_STRATEGY_RIGHT | CLAIM_REWARDS_RIGHT | WITHDRAW_RIGHT;
+
+/// Deposits only add tokens to the account, so any active delegate can make them
+pub const DEPOSIT_RIGHT: u32 = 0;
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, Debug)]
+pub struct Delegation {
+    pub rights: u32,
+    pub expiry_epoch: Epoch,
+}
+
+#[multiversx_sc::module]
+pub trait UserDelegatesModule:
+    crate::common::common_storage::CommonStorageModule + crate::contract_state::ContractStateModule
+{
+    /// Allows the delegate to act for the caller's account until expiry_epoch.
+    /// rights is a combination of:
+    ///     1 - change the compound strategy
+    ///     2 - claim rewards, which are sent to the account owner
+    ///     4 - withdraw tokens, which are sent to the account owner
+    ///
+    /// Adding an existing delegate overwrites its rights and expiry epoch.
+    #[endpoint(addDelegate)]
+    fn add_delegate(&self, delegate: ManagedAddress, rights: u32, expiry_epoch: Epoch) {
+        self.require_not_withdraw_only();
+        require!(
+            rights != 0 && rights & !ALL_DELEGATE_RIGHTS == 0,
+            "Invalid delegate rights"
+        );
+
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(expiry_epoch > current_epoch, "Invalid expiry epoch");
+
+        let caller = self.blockchain().get_caller();
+        require!(caller != delegate, "Cannot delegate to yourself");
+
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let _ = self.user_delegates(user_id).insert(
+            delegate,
+            Delegation {
+                rights,
+                expiry_epoch,
+            },
+        );
+    }
+
+    #[endpoint(removeDelegate)]
+    fn remove_delegate(&self, delegate: ManagedAddress) {
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let removed = self.user_delegates(user_id).remove(&delegate);
+        require!(removed.is_some(), "Unknown delegate");
+    }
+
+    /// Returns the account the caller acts for.
+    /// Without an on behalf of account, the caller acts for itself.
+    /// Otherwise, the caller must be a delegate of that account, with the required rights.
+    fn get_acting_user(
+        &self,
+        opt_on_behalf_of: OptionalValue<ManagedAddress>,
+        required_rights: u32,
+    ) -> ManagedAddress {
+        let caller = self.blockchain().get_caller();
+        let user = match opt_on_behalf_of {
+            OptionalValue::Some(user) => user,
+            OptionalValue::None => return caller,
+        };
+
+        let user_id = self.user_ids().get_id(&user);
+        require!(user_id != NULL_ID, "Unknown address");
+
+        let opt_delegation = self.user_delegates(user_id).get(&caller);
+        require!(opt_delegation.is_some(), "Not a delegate of the user");
+
+        let delegation = unsafe { opt_delegation.unwrap_unchecked() };
+        let current_epoch = self.blockchain().get_block_epoch();
+        require!(
+            current_epoch < delegation.expiry_epoch,
+            "Delegation expired"
+        );
+        require!(
+            delegation.rights & required_rights == required_rights,
+            "Delegate does not have the required rights"
+        );
+
+        user
+    }
+
+    #[view(getUserDelegates)]
+    fn get_user_delegates_view(
+        &self,
+        user: ManagedAddress,
+    ) -> MultiValueEncoded<MultiValue3<ManagedAddress, u32, Epoch>> {
+        let mut result = MultiValueEncoded::new();
+        let user_id = self.user_ids().get_id(&user);
+        if user_id == NULL_ID {
+            return result;
+        }
+
+        for (delegate, delegation) in self.user_delegates(user_id).iter() {
+            result.push((delegate, delegation.rights, delegation.expiry_epoch).into());
+        }
+
+        result
+    }
+
+    #[storage_mapper("userDelegates")]
+    fn user_delegates(&self, user_id: AddressId) -> MapMapper<ManagedAddress, Delegation>;
+}
@@ -1,6 +1,9 @@
 multiversx_sc::imports!();
 
-use crate::events::{DepositType, WithdrawType};
+use crate::{
+    events::{DepositType, WithdrawType},
+    user_tokens::user_delegates::{DEPOSIT_RIGHT, WITHDRAW_RIGHT},
+};
 use common_structs::PaymentsVec;
 
 #[multiversx_sc::module]
@@ -11,53 +14,223 @@ pub trait UserFarmTokensModule:
     + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
     + super::withdraw_tokens::WithdrawTokensModule
     + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + super::user_delegates::UserDelegatesModule
+    + crate::deposit_caps::DepositCapsModule
     + utils::UtilsModule
 {
     #[payable("*")]
     #[endpoint(depositFarmTokens)]
-    fn deposit_farm_tokens(&self) {
+    fn deposit_farm_tokens(&self, opt_on_behalf_of: OptionalValue<ManagedAddress>) {
+        self.require_active();
+
         let payments = self.get_non_empty_payments();
+        let user = self.get_acting_user(opt_on_behalf_of, DEPOSIT_RIGHT);
         let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_or_insert(&caller);
+        self.deposit_farm_tokens_for_user(&caller, &user, &payments);
+    }
+
+    /// Deposits the tokens in the user's account, registering the user if needed.
+    /// Each position must be at least the contract's minimum deposit-for amount.
+    /// Only the user can withdraw them afterwards.
+    #[payable("*")]
+    #[endpoint(depositFarmTokensFor)]
+    fn deposit_farm_tokens_for(&self, user: ManagedAddress) {
+        self.require_active();
+        require!(!user.is_zero(), "Invalid user address");
 
-        self.user_farm_tokens(user_id).update(|tokens| {
-            for payment in payments.iter() {
-                let farm_id = self.farm_for_farm_token(&payment.token_identifier).get();
-                require!(farm_id != NULL_ID, "Invalid token");
+        let payments = self.get_non_empty_payments();
+        self.require_min_deposit_for_amount(&payments);
 
-                tokens.push(payment.clone());
-            }
-        });
+        let caller = self.blockchain().get_caller();
+        self.deposit_farm_tokens_for_user(&caller, &user, &payments);
+    }
+
+    fn deposit_farm_tokens_for_user(
+        &self,
+        payer: &ManagedAddress,
+        user: &ManagedAddress,
+        payments: &PaymentsVec<Self::Api>,
+    ) {
+        let user_id = self.user_ids().get_id_or_insert(user);
+        self.migrate_legacy_user_farm_tokens(user_id);
+
+        for payment in payments.iter() {
+            let farm_id = self.farm_for_farm_token(&payment.token_identifier).get();
+            require!(farm_id != NULL_ID, "Invalid token");
+
+            self.add_user_farm_position(user_id, farm_id, payment.clone());
+        }
+        self.add_deposited_positions_usage(user_id, payments);
 
-        self.emit_token_deposit_event(&caller, DepositType::FarmTokens, &payments);
+        self.emit_token_deposit_event(payer, user, DepositType::FarmTokens, payments);
     }
 
     #[endpoint(withdrawAllFarmTokens)]
-    fn withdraw_all_farm_tokens_endpoint(&self) -> PaymentsVec<Self::Api> {
-        let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_non_zero(&caller);
-        let tokens_mapper = self.user_farm_tokens(user_id);
-        let withdrawn_tokens = self.withdraw_all_tokens(&caller, &tokens_mapper);
-        self.emit_token_withdrawal_event(&caller, WithdrawType::FarmTokens, &withdrawn_tokens);
+    fn withdraw_all_farm_tokens_endpoint(
+        &self,
+        opt_on_behalf_of: OptionalValue<ManagedAddress>,
+    ) -> PaymentsVec<Self::Api> {
+        let user = self.get_acting_user(opt_on_behalf_of, WITHDRAW_RIGHT);
+        let user_id = self.user_ids().get_id_non_zero(&user);
+        let withdrawn_tokens = self.withdraw_all_user_farm_tokens(&user, user_id);
+        self.emit_token_withdrawal_event(&user, WithdrawType::FarmTokens, &withdrawn_tokens);
 
         withdrawn_tokens
     }
 
+    /// Positions are selected by token identifier and nonce. Partial amounts can be withdrawn.
     #[endpoint(withdrawSpecificFarmTokens)]
-    fn withdraw_specific_farm_tokens_endpoint(&self, tokens_to_withdraw: PaymentsVec<Self::Api>) {
-        let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_non_zero(&caller);
-        let tokens_mapper = self.user_farm_tokens(user_id);
-        self.withdraw_specific_tokens(&caller, &tokens_mapper, &tokens_to_withdraw);
-        self.emit_token_withdrawal_event(&caller, WithdrawType::FarmTokens, &tokens_to_withdraw);
+    fn withdraw_specific_farm_tokens_endpoint(
+        &self,
+        tokens_to_withdraw: PaymentsVec<Self::Api>,
+        opt_on_behalf_of: OptionalValue<ManagedAddress>,
+    ) {
+        let user = self.get_acting_user(opt_on_behalf_of, WITHDRAW_RIGHT);
+        let user_id = self.user_ids().get_id_non_zero(&user);
+        self.migrate_legacy_user_farm_tokens(user_id);
+
+        self.remove_positions_usage(user_id, &tokens_to_withdraw);
+        self.remove_user_farm_tokens(user_id, &tokens_to_withdraw);
+        if !tokens_to_withdraw.is_empty() {
+            self.send().direct_multi(&user, &tokens_to_withdraw);
+        }
+
+        self.emit_token_withdrawal_event(&user, WithdrawType::FarmTokens, &tokens_to_withdraw);
     }
 
+    /// Returns the positions of all the user's farms, including the ones not yet migrated
+    /// from the legacy storage layout.
     #[view(getUserFarmTokens)]
     fn get_user_farm_tokens_view(&self, user: ManagedAddress) -> PaymentsVec<Self::Api> {
         let user_id = self.user_ids().get_id(&user);
-        self.user_farm_tokens(user_id).get()
+        if user_id == NULL_ID {
+            return PaymentsVec::new();
+        }
+
+        self.get_all_user_farm_tokens(user_id)
     }
 
+    fn get_all_user_farm_tokens(&self, user_id: AddressId) -> PaymentsVec<Self::Api> {
+        let mut all_tokens = self.user_farm_tokens(user_id).get();
+        for farm_id in self.user_farm_ids(user_id).iter() {
+            all_tokens.append_vec(self.user_farm_positions(user_id, farm_id).get());
+        }
+
+        all_tokens
+    }
+
+    /// Positions used to be stored as a single list per user.
+    /// They are moved to the per-farm layout the first time the user is touched.
+    /// Positions of farms that are no longer whitelisted are kept under NULL_ID.
+    /// The deposit usage of the legacy positions is seeded at the same time.
+    fn migrate_legacy_user_farm_tokens(&self, user_id: AddressId) {
+        let legacy_mapper = self.user_farm_tokens(user_id);
+        if legacy_mapper.is_empty() {
+            return;
+        }
+
+        let legacy_tokens = legacy_mapper.take();
+        for farm_token in legacy_tokens.iter() {
+            let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
+            self.add_user_farm_position(user_id, farm_id, farm_token.clone());
+        }
+        self.add_migrated_positions_usage(user_id, &legacy_tokens);
+    }
+
+    fn add_user_farm_position(
+        &self,
+        user_id: AddressId,
+        farm_id: AddressId,
+        farm_token: EsdtTokenPayment,
+    ) {
+        let _ = self.user_farm_ids(user_id).insert(farm_id);
+        self.user_farm_positions(user_id, farm_id)
+            .update(|positions| positions.push(farm_token));
+    }
+
+    /// Clears the farm from the user's index when no positions are left
+    fn set_user_farm_positions(
+        &self,
+        user_id: AddressId,
+        farm_id: AddressId,
+        positions: &PaymentsVec<Self::Api>,
+    ) {
+        if positions.is_empty() {
+            self.user_farm_positions(user_id, farm_id).clear();
+            let _ = self.user_farm_ids(user_id).swap_remove(&farm_id);
+        } else {
+            self.user_farm_positions(user_id, farm_id).set(positions);
+        }
+    }
+
+    /// Returns the id under which the user's position is stored.
+    /// Falls back to searching all the user's farms, for farms that are no longer whitelisted.
+    fn find_user_farm_id(&self, user_id: AddressId, farm_token: &EsdtTokenPayment) -> AddressId {
+        let user_farm_ids = self.user_farm_ids(user_id);
+        let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
+        if farm_id != NULL_ID && user_farm_ids.contains(&farm_id) {
+            return farm_id;
+        }
+
+        for user_farm_id in user_farm_ids.iter() {
+            let positions = self.user_farm_positions(user_id, user_farm_id).get();
+            let opt_index = self.find_token_in_payments(
+                &farm_token.token_identifier,
+                farm_token.token_nonce,
+                &positions,
+            );
+            if opt_index.is_some() {
+                return user_farm_id;
+            }
+        }
+
+        sc_panic!("Invalid token to withdraw");
+    }
+
+    fn remove_user_farm_tokens(&self, user_id: AddressId, farm_tokens: &PaymentsVec<Self::Api>) {
+        for farm_token in farm_tokens.iter() {
+            let farm_id = self.find_user_farm_id(user_id, &farm_token);
+            let mut positions = self.user_farm_positions(user_id, farm_id).get();
+            self.deduct_token_from_payments(&farm_token, &mut positions);
+            self.set_user_farm_positions(user_id, farm_id, &positions);
+        }
+    }
+
+    fn withdraw_all_user_farm_tokens(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+    ) -> PaymentsVec<Self::Api> {
+        self.migrate_legacy_user_farm_tokens(user_id);
+
+        let all_tokens = self.get_all_user_farm_tokens(user_id);
+        self.remove_positions_usage(user_id, &all_tokens);
+
+        let mut user_farm_ids = self.user_farm_ids(user_id);
+        for farm_id in user_farm_ids.iter() {
+            self.user_farm_positions(user_id, farm_id).clear();
+        }
+        user_farm_ids.clear();
+
+        if !all_tokens.is_empty() {
+            self.send().direct_multi(user, &all_tokens);
+        }
+
+        all_tokens
+    }
+
+    /// Legacy layout, only read when migrating users
     #[storage_mapper("userFarmTokens")]
     fn user_farm_tokens(&self, user_id: AddressId) -> SingleValueMapper<PaymentsVec<Self::Api>>;
+
+    #[storage_mapper("userFarmIds")]
+    fn user_farm_ids(&self, user_id: AddressId) -> UnorderedSetMapper<AddressId>;
+
+    #[storage_mapper("userFarmPositions")]
+    fn user_farm_positions(
+        &self,
+        user_id: AddressId,
+        farm_id: AddressId,
+    ) -> SingleValueMapper<PaymentsVec<Self::Api>>;
 }
@@ -0,0 +1,139 @@
+multiversx_sc::imports!();
+
+use crate::events::{DepositType, WithdrawType};
+use common_structs::{Epoch, PaymentsVec};
+
+#[multiversx_sc::module]
+pub trait UserLockedTokensModule:
+    crate::common::common_storage::CommonStorageModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Deposit locked tokens, so the user's fees collector rewards are claimed by the contract.
+    /// The deposited tokens keep counting towards the user's energy.
+    /// All the user's deposited locked tokens are merged into a single position.
+    /// Not available while the user compounds the locked rewards into energy.
+    #[payable("*")]
+    #[endpoint(depositLockedTokens)]
+    fn deposit_locked_tokens(&self) {
+        self.require_active();
+
+        let payments = self.get_non_empty_payments();
+        let locked_token_id = self.get_locked_token_id();
+        for payment in payments.iter() {
+            require!(payment.token_identifier == locked_token_id, "Invalid token");
+        }
+
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_or_insert(&caller);
+        require!(
+            self.user_energy_compound_lock_epochs(user_id).get() == 0,
+            "Cannot deposit locked tokens while compounding energy"
+        );
+
+        let locked_tokens_mapper = self.user_locked_tokens(user_id);
+        let mut all_locked_tokens = payments.clone();
+        if !locked_tokens_mapper.is_empty() {
+            all_locked_tokens.push(locked_tokens_mapper.get());
+        }
+
+        if let Some(merged_tokens) = self.merge_locked_tokens(caller.clone(), all_locked_tokens) {
+            locked_tokens_mapper.set(merged_tokens);
+        }
+
+        self.emit_token_deposit_event(&caller, &caller, DepositType::LockedTokens, &payments);
+    }
+
+    /// opt_amount: The amount to withdraw. If not provided, the whole position is withdrawn.
+    #[endpoint(withdrawLockedTokens)]
+    fn withdraw_locked_tokens_endpoint(
+        &self,
+        opt_amount: OptionalValue<BigUint>,
+    ) -> EsdtTokenPayment {
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let locked_tokens_mapper = self.user_locked_tokens(user_id);
+        require!(!locked_tokens_mapper.is_empty(), "No locked tokens");
+
+        let mut locked_tokens = locked_tokens_mapper.get();
+        let withdraw_amount = match opt_amount {
+            OptionalValue::Some(amount) => {
+                require!(
+                    amount > 0 && amount <= locked_tokens.amount,
+                    "Invalid amount"
+                );
+                amount
+            }
+            OptionalValue::None => locked_tokens.amount.clone(),
+        };
+
+        locked_tokens.amount -= &withdraw_amount;
+        if locked_tokens.amount > 0 {
+            locked_tokens_mapper.set(&locked_tokens);
+        } else {
+            locked_tokens_mapper.clear();
+        }
+
+        let withdrawn_tokens = EsdtTokenPayment::new(
+            locked_tokens.token_identifier,
+            locked_tokens.token_nonce,
+            withdraw_amount,
+        );
+        self.send().direct_esdt(
+            &caller,
+            &withdrawn_tokens.token_identifier,
+            withdrawn_tokens.token_nonce,
+            &withdrawn_tokens.amount,
+        );
+
+        let withdrawn_tokens_vec = ManagedVec::from_single_item(withdrawn_tokens.clone());
+        self.emit_token_withdrawal_event(
+            &caller,
+            WithdrawType::LockedTokens,
+            &withdrawn_tokens_vec,
+        );
+
+        withdrawn_tokens
+    }
+
+    fn withdraw_all_locked_tokens(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+    ) -> PaymentsVec<Self::Api> {
+        let locked_tokens_mapper = self.user_locked_tokens(user_id);
+        if locked_tokens_mapper.is_empty() {
+            return PaymentsVec::new();
+        }
+
+        let locked_tokens = locked_tokens_mapper.take();
+        self.send().direct_esdt(
+            user,
+            &locked_tokens.token_identifier,
+            locked_tokens.token_nonce,
+            &locked_tokens.amount,
+        );
+
+        ManagedVec::from_single_item(locked_tokens)
+    }
+
+    #[view(getUserLockedTokens)]
+    fn get_user_locked_tokens_view(&self, user: ManagedAddress) -> OptionalValue<EsdtTokenPayment> {
+        let user_id = self.user_ids().get_id(&user);
+        let locked_tokens_mapper = self.user_locked_tokens(user_id);
+        if user_id != NULL_ID && !locked_tokens_mapper.is_empty() {
+            OptionalValue::Some(locked_tokens_mapper.get())
+        } else {
+            OptionalValue::None
+        }
+    }
+
+    #[storage_mapper("userLockedTokens")]
+    fn user_locked_tokens(&self, user_id: AddressId) -> SingleValueMapper<EsdtTokenPayment>;
+
+    #[storage_mapper("userEnergyCompoundLockEpochs")]
+    fn user_energy_compound_lock_epochs(&self, user_id: AddressId) -> SingleValueMapper<Epoch>;
+}
@@ -0,0 +1,168 @@
+multiversx_sc::imports!();
+
+use crate::{common::unique_payments::UniquePayments, events::WithdrawType};
+use common_structs::{Epoch, PaymentsVec};
+
+#[multiversx_sc::module]
+pub trait UserPayoutsModule:
+    crate::common::common_storage::CommonStorageModule
+    + super::user_rewards::UserRewardsModule
+    + crate::fees::FeesModule
+    + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + lkmex_transfer::energy_transfer::EnergyTransferModule
+    + legacy_token_decode_module::LegacyTokenDecodeModule
+    + energy_query::EnergyQueryModule
+    + utils::UtilsModule
+{
+    /// Pairs of (token_id, min_amount). During keeper processing, accumulated rewards
+    /// of a token are sent to the user's wallet once they reach min_amount.
+    /// A min_amount of 0 removes the token's threshold.
+    #[endpoint(setPayoutThresholds)]
+    fn set_payout_thresholds(
+        &self,
+        thresholds: MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>>,
+    ) {
+        self.require_not_withdraw_only();
+
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let mut thresholds_mapper = self.user_payout_thresholds(user_id);
+        for threshold in thresholds {
+            let (token_id, min_amount) = threshold.into_tuple();
+            if min_amount == 0 {
+                let _ = thresholds_mapper.remove(&token_id);
+            } else {
+                let _ = thresholds_mapper.insert(token_id, min_amount);
+            }
+        }
+    }
+
+    /// All the accumulated rewards are sent to the user's wallet every payout_epochs epochs.
+    /// A value of 0 disables the periodic payouts.
+    #[endpoint(setPayoutFrequency)]
+    fn set_payout_frequency(&self, payout_epochs: Epoch) {
+        self.require_not_withdraw_only();
+
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let current_epoch = self.blockchain().get_block_epoch();
+        self.user_payout_frequency(user_id).set(payout_epochs);
+        self.user_last_payout_epoch(user_id).set(current_epoch);
+    }
+
+    /// Sends the rewards that reached the user's payout thresholds, or all of the rewards
+    /// if the payout frequency has passed. The rewards already had the fees taken.
+    fn process_auto_payouts(&self, user: &ManagedAddress, user_id: AddressId) {
+        let rewards_mapper = self.user_rewards(user_id);
+        if rewards_mapper.is_empty() {
+            return;
+        }
+
+        let payout_frequency = self.user_payout_frequency(user_id).get();
+        if payout_frequency > 0 {
+            let current_epoch = self.blockchain().get_block_epoch();
+            let last_payout_epoch_mapper = self.user_last_payout_epoch(user_id);
+            if current_epoch >= last_payout_epoch_mapper.get() + payout_frequency {
+                last_payout_epoch_mapper.set(current_epoch);
+
+                let sent_rewards = self.user_claim_rewards(user.clone(), user_id);
+                if !sent_rewards.is_empty() {
+                    self.emit_token_withdrawal_event(user, WithdrawType::AutoPayout, &sent_rewards);
+                }
+
+                return;
+            }
+        }
+
+        let thresholds_mapper = self.user_payout_thresholds(user_id);
+        if thresholds_mapper.is_empty() {
+            return;
+        }
+
+        let mut rewards = rewards_mapper.get();
+        let mut payout_tokens = PaymentsVec::new();
+        if let Some(locked_tokens) = rewards.opt_locked_tokens.take() {
+            if self.is_above_payout_threshold(&thresholds_mapper, &locked_tokens) {
+                payout_tokens.push(locked_tokens);
+            } else {
+                rewards.opt_locked_tokens = Some(locked_tokens);
+            }
+        }
+
+        let mut remaining_tokens = PaymentsVec::new();
+        for payment in rewards.other_tokens.into_payments().iter() {
+            if self.is_above_payout_threshold(&thresholds_mapper, &payment) {
+                payout_tokens.push(payment.clone());
+            } else {
+                remaining_tokens.push(payment.clone());
+            }
+        }
+        rewards.other_tokens = UniquePayments::new_from_unique_payments(remaining_tokens);
+
+        if payout_tokens.is_empty() {
+            return;
+        }
+
+        rewards_mapper.set(&rewards);
+        self.send().direct_multi(user, &payout_tokens);
+        self.emit_token_withdrawal_event(user, WithdrawType::AutoPayout, &payout_tokens);
+    }
+
+    fn is_above_payout_threshold(
+        &self,
+        thresholds_mapper: &MapMapper<TokenIdentifier, BigUint>,
+        payment: &EsdtTokenPayment,
+    ) -> bool {
+        match thresholds_mapper.get(&payment.token_identifier) {
+            Some(min_amount) => payment.amount >= min_amount,
+            None => false,
+        }
+    }
+
+    fn clear_user_payout_settings(&self, user_id: AddressId) {
+        self.user_payout_thresholds(user_id).clear();
+        self.user_payout_frequency(user_id).clear();
+        self.user_last_payout_epoch(user_id).clear();
+    }
+
+    #[view(getUserPayoutThresholds)]
+    fn get_user_payout_thresholds_view(
+        &self,
+        user: ManagedAddress,
+    ) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+        let user_id = self.user_ids().get_id(&user);
+        if user_id == NULL_ID {
+            return result;
+        }
+
+        for (token_id, min_amount) in self.user_payout_thresholds(user_id).iter() {
+            result.push((token_id, min_amount).into());
+        }
+
+        result
+    }
+
+    #[view(getUserPayoutFrequency)]
+    fn get_user_payout_frequency_view(&self, user: ManagedAddress) -> Epoch {
+        let user_id = self.user_ids().get_id(&user);
+        self.user_payout_frequency(user_id).get()
+    }
+
+    #[view(getUserLastPayoutEpoch)]
+    fn get_user_last_payout_epoch_view(&self, user: ManagedAddress) -> Epoch {
+        let user_id = self.user_ids().get_id(&user);
+        self.user_last_payout_epoch(user_id).get()
+    }
+
+    #[storage_mapper("userPayoutThresholds")]
+    fn user_payout_thresholds(&self, user_id: AddressId) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("userPayoutFrequency")]
+    fn user_payout_frequency(&self, user_id: AddressId) -> SingleValueMapper<Epoch>;
+
+    #[storage_mapper("userLastPayoutEpoch")]
+    fn user_last_payout_epoch(&self, user_id: AddressId) -> SingleValueMapper<Epoch>;
+}
@@ -4,6 +4,7 @@ multiversx_sc::derive_imports!();
 use crate::{
     common::rewards_wrapper::{MergedRewardsWrapper, RewardsWrapper},
     events::WithdrawType,
+    user_tokens::user_delegates::CLAIM_REWARDS_RIGHT,
 };
 use common_structs::PaymentsVec;
 use mergeable::Mergeable;
@@ -14,17 +15,21 @@ pub trait UserRewardsModule:
     + crate::fees::FeesModule
     + crate::external_sc_interactions::locked_token_merging::LockedTokenMergingModule
     + crate::events::EventsModule
+    + super::user_delegates::UserDelegatesModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
     + legacy_token_decode_module::LegacyTokenDecodeModule
     + energy_query::EnergyQueryModule
     + utils::UtilsModule
 {
     #[endpoint(userClaimRewards)]
-    fn user_claim_rewards_endpoint(&self) -> PaymentsVec<Self::Api> {
-        let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_non_zero(&caller);
-        let claimed_tokens = self.user_claim_rewards(caller.clone(), user_id);
-        self.emit_token_withdrawal_event(&caller, WithdrawType::RewardTokens, &claimed_tokens);
+    fn user_claim_rewards_endpoint(
+        &self,
+        opt_on_behalf_of: OptionalValue<ManagedAddress>,
+    ) -> PaymentsVec<Self::Api> {
+        let user = self.get_acting_user(opt_on_behalf_of, CLAIM_REWARDS_RIGHT);
+        let user_id = self.user_ids().get_id_non_zero(&user);
+        let claimed_tokens = self.user_claim_rewards(user.clone(), user_id);
+        self.emit_token_withdrawal_event(&user, WithdrawType::RewardTokens, &claimed_tokens);
 
         claimed_tokens
     }
@@ -62,6 +67,12 @@ pub trait UserRewardsModule:
         };
         self.take_fees(user.clone(), &mut merged_rew_wrapper);
 
+        let mut claimed_rewards = merged_rew_wrapper.other_tokens.clone().into_payments();
+        if let Some(locked_tokens) = &merged_rew_wrapper.opt_locked_tokens {
+            claimed_rewards.push(locked_tokens.clone());
+        }
+        self.record_claimed_rewards(user_id, &claimed_rewards);
+
         let rewards_mapper = self.user_rewards(user_id);
         if rewards_mapper.is_empty() {
             rewards_mapper.set(merged_rew_wrapper);
@@ -79,6 +90,21 @@ pub trait UserRewardsModule:
         });
     }
 
+    /// Used for the user's own tokens, returned without claiming, so no fees are taken
+    fn return_tokens_to_user_rewards(&self, user_id: AddressId, tokens: PaymentsVec<Self::Api>) {
+        let rewards_mapper = self.user_rewards(user_id);
+        let mut user_rewards = if !rewards_mapper.is_empty() {
+            rewards_mapper.get()
+        } else {
+            MergedRewardsWrapper::default()
+        };
+        for token in tokens.iter() {
+            user_rewards.other_tokens.add_payment(token.clone());
+        }
+
+        rewards_mapper.set(&user_rewards);
+    }
+
     #[storage_mapper("userRewards")]
     fn user_rewards(
         &self,
@@ -0,0 +1,45 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use super::user_delegates::CHANGE_STRATEGY_RIGHT;
+
+/// CompoundIntoStaking must stay the first variant,
+/// as it is the default for users that never set a strategy
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy, Debug)]
+pub enum CompoundStrategy {
+    CompoundIntoStaking,
+    ClaimOnly,
+    CompoundIntoLpFarm,
+    SendToWallet,
+    CompoundIntoMetastaking,
+}
+
+#[multiversx_sc::module]
+pub trait UserStrategyModule:
+    crate::common::common_storage::CommonStorageModule
+    + crate::contract_state::ContractStateModule
+    + super::user_delegates::UserDelegatesModule
+{
+    #[endpoint(setCompoundStrategy)]
+    fn set_compound_strategy(
+        &self,
+        strategy: CompoundStrategy,
+        opt_on_behalf_of: OptionalValue<ManagedAddress>,
+    ) {
+        self.require_not_withdraw_only();
+
+        let user = self.get_acting_user(opt_on_behalf_of, CHANGE_STRATEGY_RIGHT);
+        let user_id = self.user_ids().get_id_non_zero(&user);
+        self.user_strategy(user_id).set(strategy);
+    }
+
+    #[view(getUserCompoundStrategy)]
+    fn get_user_compound_strategy_view(&self, user: ManagedAddress) -> CompoundStrategy {
+        let user_id = self.user_ids().get_id(&user);
+        self.user_strategy(user_id).get()
+    }
+
+    #[storage_mapper("userStrategy")]
+    fn user_strategy(&self, user_id: AddressId) -> SingleValueMapper<CompoundStrategy>;
+}
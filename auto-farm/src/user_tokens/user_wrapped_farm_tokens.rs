@@ -0,0 +1,83 @@
+multiversx_sc::imports!();
+
+use crate::events::{DepositType, WithdrawType};
+use common_structs::PaymentsVec;
+
+#[multiversx_sc::module]
+pub trait UserWrappedFarmTokensModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule
+    + super::withdraw_tokens::WithdrawTokensModule
+    + crate::events::EventsModule
+    + crate::contract_state::ContractStateModule
+    + utils::UtilsModule
+{
+    /// Accepts proxy-dex wrapped farm tokens.
+    /// Farm-staking positions are deposited through depositFarmTokens.
+    #[payable("*")]
+    #[endpoint(depositWrappedFarmTokens)]
+    fn deposit_wrapped_farm_tokens(&self) {
+        self.require_active();
+
+        let payments = self.get_non_empty_payments();
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_or_insert(&caller);
+
+        self.user_wrapped_farm_tokens(user_id).update(|tokens| {
+            for payment in payments.iter() {
+                let proxy_dex_id = self
+                    .proxy_dex_for_wrapped_farm_token(&payment.token_identifier)
+                    .get();
+                require!(proxy_dex_id != NULL_ID, "Invalid token");
+
+                tokens.push(payment.clone());
+            }
+        });
+
+        self.emit_token_deposit_event(&caller, &caller, DepositType::WrappedFarmTokens, &payments);
+    }
+
+    #[endpoint(withdrawAllWrappedFarmTokens)]
+    fn withdraw_all_wrapped_farm_tokens_endpoint(&self) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let tokens_mapper = self.user_wrapped_farm_tokens(user_id);
+        let withdrawn_tokens = self.withdraw_all_tokens(&caller, &tokens_mapper);
+        self.emit_token_withdrawal_event(
+            &caller,
+            WithdrawType::WrappedFarmTokens,
+            &withdrawn_tokens,
+        );
+
+        withdrawn_tokens
+    }
+
+    #[endpoint(withdrawSpecificWrappedFarmTokens)]
+    fn withdraw_specific_wrapped_farm_tokens_endpoint(
+        &self,
+        tokens_to_withdraw: PaymentsVec<Self::Api>,
+    ) {
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let tokens_mapper = self.user_wrapped_farm_tokens(user_id);
+        self.withdraw_specific_tokens(&caller, &tokens_mapper, &tokens_to_withdraw);
+        self.emit_token_withdrawal_event(
+            &caller,
+            WithdrawType::WrappedFarmTokens,
+            &tokens_to_withdraw,
+        );
+    }
+
+    #[view(getUserWrappedFarmTokens)]
+    fn get_user_wrapped_farm_tokens_view(&self, user: ManagedAddress) -> PaymentsVec<Self::Api> {
+        let user_id = self.user_ids().get_id(&user);
+        self.user_wrapped_farm_tokens(user_id).get()
+    }
+
+    #[storage_mapper("userWrappedFarmTokens")]
+    fn user_wrapped_farm_tokens(
+        &self,
+        user_id: AddressId,
+    ) -> SingleValueMapper<PaymentsVec<Self::Api>>;
+}
@@ -16,22 +16,7 @@ pub trait WithdrawTokensModule {
 
         let mut all_tokens = tokens_mapper.get();
         for ttw in tokens_to_withdraw {
-            let opt_index = self.find_token_in_payments(&ttw.token_identifier, &all_tokens);
-            require!(opt_index.is_some(), "Invalid token to withdraw");
-
-            let index = unsafe { opt_index.unwrap_unchecked() };
-            let mut full_token = all_tokens.get(index).clone();
-            require!(
-                ttw.amount <= full_token.amount,
-                "Not enough balance to withdraw"
-            );
-
-            full_token.amount -= &ttw.amount;
-            if full_token.amount > 0 {
-                let _ = all_tokens.set(index, full_token);
-            } else {
-                all_tokens.remove(index);
-            }
+            self.deduct_token_from_payments(&ttw, &mut all_tokens);
         }
 
         tokens_mapper.set(&all_tokens);
@@ -39,6 +24,30 @@ pub trait WithdrawTokensModule {
         self.send().direct_multi(user, tokens_to_withdraw);
     }
 
+    fn deduct_token_from_payments(
+        &self,
+        token: &EsdtTokenPayment,
+        payments: &mut PaymentsVec<Self::Api>,
+    ) {
+        let opt_index =
+            self.find_token_in_payments(&token.token_identifier, token.token_nonce, payments);
+        require!(opt_index.is_some(), "Invalid token to withdraw");
+
+        let index = unsafe { opt_index.unwrap_unchecked() };
+        let mut full_token = payments.get(index).clone();
+        require!(
+            token.amount <= full_token.amount,
+            "Not enough balance to withdraw"
+        );
+
+        full_token.amount -= &token.amount;
+        if full_token.amount > 0 {
+            let _ = payments.set(index, full_token);
+        } else {
+            payments.remove(index);
+        }
+    }
+
     fn withdraw_all_tokens(
         &self,
         user: &ManagedAddress,
@@ -56,10 +65,11 @@ pub trait WithdrawTokensModule {
     fn find_token_in_payments(
         &self,
         token_id: &TokenIdentifier,
+        token_nonce: u64,
         payments: &PaymentsVec<Self::Api>,
     ) -> Option<usize> {
         for (i, payment) in payments.iter().enumerate() {
-            if &payment.token_identifier == token_id {
+            if &payment.token_identifier == token_id && payment.token_nonce == token_nonce {
                 return Some(i);
             }
         }
@@ -1,7 +1,5 @@
 multiversx_sc::imports!();
 
-use common_structs::PaymentsVec;
-
 #[multiversx_sc::module]
 pub trait FarmsWhitelistModule:
     read_external_storage::ReadExternalStorageModule
@@ -42,10 +40,17 @@ pub trait FarmsWhitelistModule:
                 continue;
             }
 
+            // the tokens may already be associated with another farm
             let farm_config = self.get_farm_config(farm_addr);
-            self.farm_for_farm_token(&farm_config.farm_token_id).clear();
-            self.farm_for_farming_token(&farm_config.farming_token_id)
-                .clear();
+            let farm_token_mapper = self.farm_for_farm_token(&farm_config.farm_token_id);
+            if farm_token_mapper.get() == prev_id {
+                farm_token_mapper.clear();
+            }
+
+            let farming_token_mapper = self.farm_for_farming_token(&farm_config.farming_token_id);
+            if farming_token_mapper.get() == prev_id {
+                farming_token_mapper.clear();
+            }
         }
     }
 
@@ -69,19 +74,6 @@ pub trait FarmsWhitelistModule:
         self.farm_ids().get_address(farm_id).into()
     }
 
-    fn get_farm_ids_for_farm_tokens(
-        &self,
-        user_farm_tokens: &PaymentsVec<Self::Api>,
-    ) -> ManagedVec<AddressId> {
-        let mut ids = ManagedVec::new();
-        for farm_token in user_farm_tokens {
-            let farm_id = self.farm_for_farm_token(&farm_token.token_identifier).get();
-            ids.push(farm_id);
-        }
-
-        ids
-    }
-
     #[storage_mapper("farmIds")]
     fn farm_ids(&self) -> AddressToIdMapper<Self::Api>;
 
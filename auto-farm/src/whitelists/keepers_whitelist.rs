@@ -0,0 +1,42 @@
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait KeepersWhitelistModule:
+    crate::common::common_storage::CommonStorageModule + crate::fees::FeesModule
+{
+    /// Keepers can call the endpoints that process users
+    #[only_owner]
+    #[endpoint(addKeepers)]
+    fn add_keepers(&self, keepers: MultiValueEncoded<ManagedAddress>) {
+        let mut keepers_mapper = self.keepers();
+        for keeper in keepers {
+            let _ = keepers_mapper.insert(keeper);
+        }
+    }
+
+    /// The pending compensation of the removed keepers is moved to the treasury's fees
+    #[only_owner]
+    #[endpoint(removeKeepers)]
+    fn remove_keepers(&self, keepers: MultiValueEncoded<ManagedAddress>) {
+        let mut keepers_mapper = self.keepers();
+        for keeper in keepers {
+            let _ = keepers_mapper.swap_remove(&keeper);
+
+            let pending_mapper = self.pending_keeper_compensation(&keeper);
+            if pending_mapper.is_empty() {
+                continue;
+            }
+
+            let pending_tokens = pending_mapper.take().into_payments();
+            let locked_token_id = self.get_locked_token_id();
+            let treasury_address = self.treasury_address().get();
+            self.add_fees(
+                &keeper,
+                &locked_token_id,
+                treasury_address,
+                self.accumulated_fees(),
+                pending_tokens,
+            );
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod farms_whitelist;
+pub mod keepers_whitelist;
 pub mod metastaking_whitelist;
+pub mod proxy_dex_whitelist;
@@ -0,0 +1,61 @@
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ProxyDexWhitelistModule:
+    read_external_storage::ReadExternalStorageModule + utils::UtilsModule
+{
+    #[label("proxy-dex-whitelist-endpoints")]
+    #[only_owner]
+    #[endpoint(addProxyDexScs)]
+    fn add_proxy_dex_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        let ids_mapper = self.proxy_dex_ids();
+        for sc_addr in scs {
+            self.require_sc_address(&sc_addr);
+
+            let new_id = ids_mapper.insert_new(&sc_addr);
+            let wrapped_farm_token_id = self.get_wrapped_farm_token_id_mapper(sc_addr).get();
+            self.require_valid_token_id(&wrapped_farm_token_id);
+
+            self.proxy_dex_for_wrapped_farm_token(&wrapped_farm_token_id)
+                .set(new_id);
+        }
+    }
+
+    #[label("proxy-dex-whitelist-endpoints")]
+    #[only_owner]
+    #[endpoint(removeProxyDexScs)]
+    fn remove_proxy_dex_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        let ids_mapper = self.proxy_dex_ids();
+        for sc_addr in scs {
+            let prev_id = ids_mapper.remove_by_address(&sc_addr);
+            if prev_id == NULL_ID {
+                continue;
+            }
+
+            let wrapped_farm_token_id = self.get_wrapped_farm_token_id_mapper(sc_addr).get();
+            self.proxy_dex_for_wrapped_farm_token(&wrapped_farm_token_id)
+                .clear();
+        }
+    }
+
+    #[label("proxy-dex-whitelist-endpoints")]
+    #[view(getProxyDexForWrappedFarmToken)]
+    fn get_proxy_dex_for_wrapped_farm_token_view(
+        &self,
+        wrapped_farm_token_id: TokenIdentifier,
+    ) -> OptionalValue<ManagedAddress> {
+        let proxy_dex_id = self
+            .proxy_dex_for_wrapped_farm_token(&wrapped_farm_token_id)
+            .get();
+        self.proxy_dex_ids().get_address(proxy_dex_id).into()
+    }
+
+    #[storage_mapper("proxyDexIds")]
+    fn proxy_dex_ids(&self) -> AddressToIdMapper<Self::Api>;
+
+    #[storage_mapper("proxyDexForWrappedFarmToken")]
+    fn proxy_dex_for_wrapped_farm_token(
+        &self,
+        wrapped_farm_token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<AddressId>;
+}
@@ -0,0 +1,132 @@
+#![allow(deprecated)]
+#![allow(dead_code)]
+
+use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
+use auto_farm::AutoFarm;
+use energy_factory::locked_token_transfer::LockedTokenTransferModule;
+use multiversx_sc::types::{Address, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, rust_biguint, testing_framework::ContractObjWrapper, DebugApi,
+};
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::FarmSetup;
+
+pub const FEE_PERCENTAGE: u64 = 1_000; // 10%
+
+pub const FIRST_FARM_INDEX: usize = 0;
+pub const SECOND_FARM_INDEX: usize = 1;
+
+pub struct AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>
+where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+{
+    pub farm_setup: FarmSetup<FarmBuilder, EnergyFactoryBuilder>,
+    pub proxy_address: Address,
+    pub auto_farm_wrapper: ContractObjWrapper<auto_farm::ContractObj<DebugApi>, AutoFarmBuilder>,
+}
+
+impl<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>
+    AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>
+where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+{
+    pub fn new(
+        farm_builder: FarmBuilder,
+        energy_factory_builder: EnergyFactoryBuilder,
+        auto_farm_builder: AutoFarmBuilder,
+    ) -> Self {
+        let rust_zero = rust_biguint!(0);
+        let farm_setup = FarmSetup::new(farm_builder, energy_factory_builder);
+        let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+
+        let proxy_address = farm_setup
+            .b_mock
+            .borrow_mut()
+            .create_user_account(&rust_zero);
+        let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+            &rust_zero,
+            Some(&farm_setup.owner),
+            auto_farm_builder,
+            "auto farm",
+        );
+
+        farm_setup
+            .b_mock
+            .borrow_mut()
+            .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+                sc.init(
+                    managed_address!(&proxy_address),
+                    FEE_PERCENTAGE,
+                    managed_address!(&energy_factory_addr),
+                    managed_address!(&energy_factory_addr), // unused here
+                );
+            })
+            .assert_ok();
+
+        AutoFarmSetup {
+            farm_setup,
+            proxy_address,
+            auto_farm_wrapper,
+        }
+    }
+
+    /// Whitelists the farms in auto-farm, and auto-farm in the farms and the energy factory
+    pub fn add_farms(&mut self) {
+        let rust_zero = rust_biguint!(0);
+        let mut farms = Vec::new();
+        for farm_wrapper in &self.farm_setup.farm_wrappers {
+            farms.push(farm_wrapper.address_ref().clone());
+        }
+
+        self.farm_setup
+            .b_mock
+            .borrow_mut()
+            .execute_tx(
+                &self.farm_setup.owner,
+                &self.auto_farm_wrapper,
+                &rust_zero,
+                |sc| {
+                    let mut args = MultiValueEncoded::new();
+                    for farm in &farms {
+                        args.push(managed_address!(farm));
+                    }
+                    sc.add_farms(args);
+                },
+            )
+            .assert_ok();
+
+        for farm_wrapper in &self.farm_setup.farm_wrappers {
+            self.farm_setup
+                .b_mock
+                .borrow_mut()
+                .execute_tx(&self.farm_setup.owner, farm_wrapper, &rust_zero, |sc| {
+                    sc.add_sc_address_to_whitelist(managed_address!(self
+                        .auto_farm_wrapper
+                        .address_ref()));
+                })
+                .assert_ok();
+        }
+
+        self.farm_setup
+            .b_mock
+            .borrow_mut()
+            .execute_tx(
+                &self.farm_setup.owner,
+                &self.farm_setup.energy_factory_wrapper,
+                &rust_zero,
+                |sc| {
+                    sc.add_to_token_transfer_whitelist(
+                        ManagedVec::from_single_item(managed_address!(self
+                            .auto_farm_wrapper
+                            .address_ref()))
+                        .into(),
+                    );
+                },
+            )
+            .assert_ok();
+    }
+}
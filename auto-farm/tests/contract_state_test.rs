@@ -0,0 +1,111 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::contract_state::{ContractState, ContractStateModule};
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::user_strategy::{CompoundStrategy, UserStrategyModule};
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::MultiValueEncoded;
+use multiversx_sc_scenario::{managed_address, rust_biguint};
+
+#[test]
+fn contract_state_test() {
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user.clone();
+    let second_user = farm_setup.second_user.clone();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+        })
+        .assert_ok();
+
+    // user try change state
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_contract_state(ContractState::ProcessingPaused);
+        })
+        .assert_user_error("Permission denied");
+
+    // pause processing
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_contract_state(ContractState::ProcessingPaused);
+
+            assert_eq!(sc.contract_state().get(), ContractState::ProcessingPaused);
+        })
+        .assert_ok();
+
+    // keeper try process users
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+        })
+        .assert_user_error("Contract is not active");
+
+    // users can still change settings
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(CompoundStrategy::ClaimOnly, OptionalValue::None);
+        })
+        .assert_ok();
+
+    // withdraw only
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_contract_state(ContractState::WithdrawOnly);
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&second_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+        })
+        .assert_user_error("Contract is in withdraw only mode");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(CompoundStrategy::SendToWallet, OptionalValue::None);
+        })
+        .assert_user_error("Contract is in withdraw only mode");
+
+    // unregister always works
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_all_and_unregister();
+        })
+        .assert_ok();
+}
@@ -0,0 +1,404 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::deposit_caps::DepositCapsModule;
+use auto_farm::external_sc_interactions::farm_actions::FarmActionsModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX, SECOND_FARM_INDEX};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec};
+use multiversx_sc_scenario::testing_framework::TxTokenTransfer;
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use tests_common::farm_with_locked_rewards_setup::{FARMING_TOKEN_ID, FARM_TOKEN_ID};
+
+#[test]
+fn deposit_caps_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let second_user = af_setup.farm_setup.second_user.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    let first_farm_token_amount = 100_000_000;
+    let second_farm_token_amount = 50_000_000;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, first_farm_token_amount);
+    af_setup
+        .farm_setup
+        .enter_farm(SECOND_FARM_INDEX, &first_user, second_farm_token_amount);
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &second_user, first_farm_token_amount);
+
+    let farm_tokens = [
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[FIRST_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(first_farm_token_amount),
+        },
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[SECOND_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(second_farm_token_amount),
+        },
+    ];
+
+    // only the owner can set caps, and only for whitelisted contracts
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(0),
+                managed_biguint!(1),
+            );
+        })
+        .assert_user_error("Endpoint can only be called by owner");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_user),
+                managed_biguint!(0),
+                managed_biguint!(1),
+            );
+        })
+        .assert_user_error("Unknown farm or metastaking address");
+
+    // user cap below the deposited amount
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(0),
+                managed_biguint!(first_farm_token_amount - 1),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            &farm_tokens,
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_user_error("User deposit cap exceeded");
+
+    // the whole user cap can be used
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(first_farm_token_amount + 1),
+                managed_biguint!(first_farm_token_amount),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            &farm_tokens,
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+
+                let (usage, cap) = sc
+                    .get_user_deposit_usage(
+                        managed_address!(&first_user),
+                        managed_address!(&first_farm),
+                    )
+                    .into_tuple();
+                assert_eq!(usage, managed_biguint!(first_farm_token_amount));
+                assert_eq!(cap, managed_biguint!(first_farm_token_amount));
+            },
+        )
+        .assert_ok();
+
+    // the second user's deposit goes over the total cap
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &second_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            2,
+            &rust_biguint!(first_farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_user_error("Deposit cap exceeded");
+
+    // withdrawing frees the usage
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_all_farm_tokens_endpoint(OptionalValue::None);
+
+            let (total_usage, _) = sc
+                .get_total_deposit_usage(managed_address!(&first_farm))
+                .into_tuple();
+            assert_eq!(total_usage, managed_biguint!(0));
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &second_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            2,
+            &rust_biguint!(first_farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn compounding_stops_at_cap_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    let farm_token_amount = 100_000_000;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, farm_token_amount);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(0),
+                managed_biguint!(farm_token_amount),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    // the user is at the cap, so the new tokens are not compounded
+    let compound_amount = 1_000;
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &af_setup.proxy_address,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let user_id = 1;
+                assert!(!sc.can_compound_into(user_id, &managed_address!(&first_farm)));
+
+                let new_tokens = EsdtTokenPayment::new(
+                    managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]),
+                    0,
+                    managed_biguint!(compound_amount),
+                );
+                let compound_result = sc.compound_staking_rewards_with_existing_farm_position(
+                    &managed_address!(&first_user),
+                    user_id,
+                    new_tokens,
+                );
+                assert!(compound_result.is_err());
+
+                let expected_positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                    1,
+                    managed_biguint!(farm_token_amount),
+                ));
+                assert_eq!(sc.user_farm_positions(user_id, 1).get(), expected_positions);
+
+                let (usage, _) = sc
+                    .get_user_deposit_usage(
+                        managed_address!(&first_user),
+                        managed_address!(&first_farm),
+                    )
+                    .into_tuple();
+                assert_eq!(usage, managed_biguint!(farm_token_amount));
+            },
+        )
+        .assert_ok();
+
+    // compounding is exempt from the cap: it continues while under the cap,
+    // and the compounded amount is added in full, going over the cap
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(0),
+                managed_biguint!(farm_token_amount + 1),
+            );
+
+            let user_id = 1;
+            assert!(sc.can_compound_into(user_id, &managed_address!(&first_farm)));
+
+            sc.add_compounded_usage(
+                user_id,
+                &managed_address!(&first_farm),
+                &managed_biguint!(compound_amount),
+            );
+            assert!(!sc.can_compound_into(user_id, &managed_address!(&first_farm)));
+
+            let (usage, cap) = sc
+                .get_user_deposit_usage(
+                    managed_address!(&first_user),
+                    managed_address!(&first_farm),
+                )
+                .into_tuple();
+            assert_eq!(usage, managed_biguint!(farm_token_amount + compound_amount));
+            assert_eq!(cap, managed_biguint!(farm_token_amount + 1));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn min_deposit_for_amount_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let partner = af_setup.farm_setup.first_user.clone();
+    let beneficiary = af_setup.farm_setup.second_user.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    let farm_token_amount = 100_000_000;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &partner, farm_token_amount);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_deposit_for_amount(
+                managed_address!(&first_farm),
+                managed_biguint!(farm_token_amount + 1),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &partner,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens_for(managed_address!(&beneficiary));
+            },
+        )
+        .assert_user_error("Deposit amount too low");
+
+    // the minimum does not apply to the user's own deposits
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &partner,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount / 2),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_deposit_for_amount(
+                managed_address!(&first_farm),
+                managed_biguint!(farm_token_amount / 2),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &partner,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount / 2),
+            |sc| {
+                sc.deposit_farm_tokens_for(managed_address!(&beneficiary));
+
+                let (usage, _) = sc
+                    .get_user_deposit_usage(
+                        managed_address!(&beneficiary),
+                        managed_address!(&first_farm),
+                    )
+                    .into_tuple();
+                assert_eq!(usage, managed_biguint!(farm_token_amount / 2));
+            },
+        )
+        .assert_ok();
+}
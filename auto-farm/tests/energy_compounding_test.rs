@@ -0,0 +1,404 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::MergedRewardsWrapper;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::external_sc_interactions::energy_compounding::EnergyCompoundingModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::user_locked_tokens::UserLockedTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm_setup::AutoFarmSetup;
+use energy_factory::SimpleLockEnergy;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::codec::Empty;
+use multiversx_sc::types::{Address, EsdtLocalRole, EsdtTokenPayment};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::{
+    LOCKED_REWARD_TOKEN_ID, LOCK_OPTIONS, REWARD_TOKEN_ID,
+};
+
+fn setup_energy_compounding<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>(
+    af_setup: &mut AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>,
+    user: &Address,
+    locked_amount: u64,
+) -> u64
+where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0);
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+    let energy_factory_wrapper = &af_setup.farm_setup.energy_factory_wrapper;
+
+    b_mock.borrow_mut().set_esdt_local_roles(
+        energy_factory_wrapper.address_ref(),
+        REWARD_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, energy_factory_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+
+    // user locks MEX for the shortest lock option, returns the locked token nonce
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(user, REWARD_TOKEN_ID, &rust_biguint!(locked_amount));
+
+    let mut locked_token_nonce = 0;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            user,
+            energy_factory_wrapper,
+            REWARD_TOKEN_ID,
+            0,
+            &rust_biguint!(locked_amount),
+            |sc| {
+                let locked_tokens = sc.lock_tokens_endpoint(LOCK_OPTIONS[0], OptionalValue::None);
+                locked_token_nonce = locked_tokens.token_nonce;
+            },
+        )
+        .assert_ok();
+
+    locked_token_nonce
+}
+
+#[test]
+fn set_energy_compound_lock_epochs_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user;
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+        })
+        .assert_ok();
+
+    // not one of the energy factory's lock options
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_energy_compound_lock_epochs(LOCK_OPTIONS[0] + 1);
+        })
+        .assert_user_error("Invalid lock epochs");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_energy_compound_lock_epochs(LOCK_OPTIONS[1]);
+
+            assert_eq!(
+                sc.get_user_energy_compound_lock_epochs_view(managed_address!(&first_user)),
+                LOCK_OPTIONS[1]
+            );
+        })
+        .assert_ok();
+
+    // 0 disables energy compounding
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_energy_compound_lock_epochs(0);
+
+            assert_eq!(
+                sc.get_user_energy_compound_lock_epochs_view(managed_address!(&first_user)),
+                0
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn energy_compound_extends_lock_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let locked_amount = 1_000;
+    let locked_token_nonce = setup_energy_compounding(&mut af_setup, &first_user, locked_amount);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+            sc.set_energy_compound_lock_epochs(LOCK_OPTIONS[1]);
+        })
+        .assert_ok();
+
+    // the locked tokens are held by auto-farm as the user's rewards
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            LOCKED_REWARD_TOKEN_ID,
+            locked_token_nonce,
+            &rust_biguint!(locked_amount),
+            |sc| {
+                let user_id = 1;
+                sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                    opt_locked_tokens: Some(EsdtTokenPayment::new(
+                        managed_token_id!(LOCKED_REWARD_TOKEN_ID),
+                        locked_token_nonce,
+                        managed_biguint!(locked_amount),
+                    )),
+                    other_tokens: UniquePayments::new(),
+                });
+
+                sc.compound_locked_rewards_into_energy(&managed_address!(&first_user), user_id);
+
+                assert_eq!(sc.user_rewards(user_id).get().opt_locked_tokens, None);
+                assert!(sc.user_locked_tokens(user_id).is_empty());
+
+                let last_compound = sc.last_energy_compound(user_id).get();
+                assert!(last_compound.energy_added > 0);
+            },
+        )
+        .assert_ok();
+
+    // the extended tokens are sent to the user, with a new unlock epoch
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user,
+        LOCKED_REWARD_TOKEN_ID,
+        locked_token_nonce + 1,
+        &rust_biguint!(locked_amount),
+        None,
+    );
+}
+
+#[test]
+fn energy_compound_rejected_with_deposited_locked_tokens_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let locked_amount = 2_000;
+    let deposit_amount = 1_500;
+    let locked_token_nonce = setup_energy_compounding(&mut af_setup, &first_user, locked_amount);
+
+    // energy compounding cannot be enabled while locked tokens are deposited
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            LOCKED_REWARD_TOKEN_ID,
+            locked_token_nonce,
+            &rust_biguint!(deposit_amount),
+            |sc| {
+                sc.deposit_locked_tokens();
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_energy_compound_lock_epochs(LOCK_OPTIONS[1]);
+        })
+        .assert_user_error("Cannot compound energy with deposited locked tokens");
+
+    // after withdrawing, energy compounding can be enabled, and deposits are rejected
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_locked_tokens_endpoint(OptionalValue::None);
+            sc.set_energy_compound_lock_epochs(LOCK_OPTIONS[1]);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            LOCKED_REWARD_TOKEN_ID,
+            locked_token_nonce,
+            &rust_biguint!(deposit_amount),
+            |sc| {
+                sc.deposit_locked_tokens();
+            },
+        )
+        .assert_user_error("Cannot deposit locked tokens while compounding energy");
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user,
+        LOCKED_REWARD_TOKEN_ID,
+        locked_token_nonce,
+        &rust_biguint!(locked_amount),
+        None,
+    );
+}
+
+#[test]
+fn deposit_and_withdraw_locked_tokens_test() {
+    DebugApi::dummy();
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let locked_amount = 2_000;
+    let deposit_amount = 1_000;
+    let locked_token_nonce = setup_energy_compounding(&mut af_setup, &first_user, locked_amount);
+
+    // only locked tokens are accepted
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(deposit_amount),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            REWARD_TOKEN_ID,
+            0,
+            &rust_biguint!(deposit_amount),
+            |sc| {
+                sc.deposit_locked_tokens();
+            },
+        )
+        .assert_user_error("Invalid token");
+
+    // two deposits are merged into a single position
+    for _ in 0..2 {
+        b_mock
+            .borrow_mut()
+            .execute_esdt_transfer(
+                &first_user,
+                &af_setup.auto_farm_wrapper,
+                LOCKED_REWARD_TOKEN_ID,
+                locked_token_nonce,
+                &rust_biguint!(deposit_amount),
+                |sc| {
+                    sc.deposit_locked_tokens();
+                },
+            )
+            .assert_ok();
+    }
+
+    let mut merged_nonce = 0;
+    b_mock
+        .borrow_mut()
+        .execute_query(&af_setup.auto_farm_wrapper, |sc| {
+            let opt_locked_tokens = sc.get_user_locked_tokens_view(managed_address!(&first_user));
+            let locked_tokens = match opt_locked_tokens {
+                OptionalValue::Some(locked_tokens) => locked_tokens,
+                OptionalValue::None => panic!("No locked tokens"),
+            };
+            assert_eq!(
+                locked_tokens.token_identifier,
+                managed_token_id!(LOCKED_REWARD_TOKEN_ID)
+            );
+            assert_eq!(locked_tokens.amount, managed_biguint!(locked_amount));
+
+            merged_nonce = locked_tokens.token_nonce;
+        })
+        .assert_ok();
+
+    // partial withdraw
+    let withdraw_amount = 500;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_locked_tokens_endpoint(OptionalValue::Some(managed_biguint!(
+                locked_amount + 1
+            )));
+        })
+        .assert_user_error("Invalid amount");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let withdrawn_tokens = sc.withdraw_locked_tokens_endpoint(OptionalValue::Some(
+                managed_biguint!(withdraw_amount),
+            ));
+            assert_eq!(withdrawn_tokens.amount, managed_biguint!(withdraw_amount));
+
+            let user_id = 1;
+            assert_eq!(
+                sc.user_locked_tokens(user_id).get().amount,
+                managed_biguint!(locked_amount - withdraw_amount)
+            );
+        })
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user,
+        LOCKED_REWARD_TOKEN_ID,
+        merged_nonce,
+        &rust_biguint!(withdraw_amount),
+        None,
+    );
+
+    // withdraw the rest
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_locked_tokens_endpoint(OptionalValue::None);
+
+            assert!(sc.user_locked_tokens(1).is_empty());
+        })
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user,
+        LOCKED_REWARD_TOKEN_ID,
+        merged_nonce,
+        &rust_biguint!(locked_amount),
+        None,
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_locked_tokens_endpoint(OptionalValue::None);
+        })
+        .assert_user_error("No locked tokens");
+}
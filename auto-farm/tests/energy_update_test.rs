@@ -0,0 +1,175 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod fees_collector_setup;
+
+use auto_farm::external_sc_interactions::energy_update::EnergyUpdateModule;
+use auto_farm::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX, SECOND_FARM_INDEX};
+use fees_collector_setup::setup_fees_collector;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc_scenario::{managed_address, managed_biguint, rust_biguint, DebugApi};
+use pausable::{PausableModule, State};
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::FARM_TOKEN_ID;
+
+#[test]
+fn update_energy_all_contracts_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let second_user = af_setup.farm_setup.second_user.clone();
+    let third_user = af_setup.farm_setup.third_user.clone();
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let second_farm = af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    let fc_wrapper = setup_fees_collector(
+        &mut b_mock.borrow_mut(),
+        fees_collector::contract_obj,
+        &energy_factory_addr,
+        &first_user,
+        &second_user,
+        &third_user,
+    );
+    let fc_addr = fc_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fc_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.fees_collector_sc_address()
+                .set(managed_address!(&fc_addr));
+        })
+        .assert_ok();
+
+    // the user only holds a position in the first farm
+    b_mock.borrow_mut().set_block_epoch(5);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 5, 1);
+
+    let farm_token_amount = 100_000_000;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, farm_token_amount);
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    // only the contracts where the user holds positions, and the fees collector,
+    // receive the user's energy
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let updated_contracts =
+                sc.update_energy_all_contracts(&managed_address!(&first_user), user_id);
+            assert_eq!(updated_contracts.len(), 2);
+            assert_eq!(*updated_contracts.get(0), managed_address!(&first_farm));
+            assert_eq!(*updated_contracts.get(1), managed_address!(&fc_addr));
+            assert_eq!(
+                sc.user_last_updated_energy(user_id)
+                    .get(&managed_address!(&first_farm)),
+                Some((5, managed_biguint!(1_000)))
+            );
+            assert_eq!(
+                sc.user_last_updated_energy(user_id)
+                    .get(&managed_address!(&second_farm)),
+                None
+            );
+
+            // unchanged energy is not sent again in the same epoch
+            let updated_contracts =
+                sc.update_energy_all_contracts(&managed_address!(&first_user), user_id);
+            assert!(updated_contracts.is_empty());
+        })
+        .assert_ok();
+
+    // the same energy amount is sent again in a new epoch
+    b_mock.borrow_mut().set_block_epoch(6);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 6, 1);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let updated_contracts =
+                sc.update_energy_all_contracts(&managed_address!(&first_user), user_id);
+            assert_eq!(updated_contracts.len(), 2);
+            assert_eq!(
+                sc.user_last_updated_energy(user_id)
+                    .get(&managed_address!(&first_farm)),
+                Some((6, managed_biguint!(1_000)))
+            );
+        })
+        .assert_ok();
+
+    // inactive farms are skipped
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.state().set(State::Inactive);
+            },
+        )
+        .assert_ok();
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 2_000, 6, 1);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let updated_contracts =
+                sc.update_energy_all_contracts(&managed_address!(&first_user), user_id);
+            assert_eq!(updated_contracts.len(), 1);
+            assert_eq!(*updated_contracts.get(0), managed_address!(&fc_addr));
+            assert_eq!(
+                sc.user_last_updated_energy(user_id)
+                    .get(&managed_address!(&first_farm)),
+                Some((6, managed_biguint!(1_000)))
+            );
+        })
+        .assert_ok();
+}
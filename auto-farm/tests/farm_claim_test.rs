@@ -6,6 +6,7 @@ use common_structs::FarmTokenAttributes;
 use energy_factory::energy::EnergyModule;
 use energy_factory::locked_token_transfer::LockedTokenTransferModule;
 use energy_query::Energy;
+use multiversx_sc::codec::multi_types::OptionalValue;
 use multiversx_sc::codec::Empty;
 use multiversx_sc::types::{BigInt, EsdtTokenPayment, ManagedVec, MultiValueEncoded};
 use multiversx_sc_scenario::testing_framework::TxTokenTransfer;
@@ -15,8 +16,10 @@ use multiversx_sc_scenario::{
 use sc_whitelist_module::SCWhitelistModule;
 use simple_lock::locked_token::LockedTokenAttributes;
 
+use auto_farm::deposit_caps::DepositCapsModule;
 use auto_farm::external_sc_interactions::farm_actions::FarmActionsModule;
 use auto_farm::fees::FeesModule;
+use auto_farm::stats::{LifetimeStats, StatsModule};
 use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
 use auto_farm::user_tokens::user_rewards::UserRewardsModule;
 use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
@@ -254,7 +257,7 @@ fn claim_rewards_through_auto_farm() {
         .b_mock
         .borrow_mut()
         .execute_esdt_multi_transfer(&first_user, &auto_farm_wrapper, &farm_tokens, |sc| {
-            sc.deposit_farm_tokens();
+            sc.deposit_farm_tokens(OptionalValue::None);
         })
         .assert_ok();
 
@@ -308,6 +311,24 @@ fn claim_rewards_through_auto_farm() {
                 other_tokens: UniquePayments::new(),
             };
             assert_eq!(accumulated_fees, expected_fees);
+
+            // check lifetime stats
+            let user_stats = sc.get_user_lifetime_stats(managed_address!(&first_user));
+            let expected_user_stats = LifetimeStats::<DebugApi> {
+                claimed_rewards: ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(LOCKED_REWARD_TOKEN_ID),
+                    0,
+                    managed_biguint!(expected_user_rewards_amount),
+                )),
+                compounded_rewards: ManagedVec::new(),
+                fees_taken: ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(LOCKED_REWARD_TOKEN_ID),
+                    0,
+                    managed_biguint!(expected_fee_amount),
+                )),
+            };
+            assert_eq!(user_stats, expected_user_stats);
+            assert_eq!(sc.get_global_lifetime_stats(), expected_user_stats);
         })
         .assert_ok();
 
@@ -441,7 +462,7 @@ fn withdraw_specific_farm_tokens_test() {
         .b_mock
         .borrow_mut()
         .execute_esdt_multi_transfer(&first_user, &auto_farm_wrapper, &farm_tokens, |sc| {
-            sc.deposit_farm_tokens();
+            sc.deposit_farm_tokens(OptionalValue::None);
         })
         .assert_ok();
 
@@ -462,7 +483,7 @@ fn withdraw_specific_farm_tokens_test() {
                 managed_biguint!(second_farm_token_amount / 4),
             ));
 
-            sc.withdraw_specific_farm_tokens_endpoint(tokens_to_withdraw);
+            sc.withdraw_specific_farm_tokens_endpoint(tokens_to_withdraw, OptionalValue::None);
 
             // check remaining farm tokens storage
             let user_farm_tokens = sc.get_user_farm_tokens_view(managed_address!(&first_user));
@@ -496,4 +517,298 @@ fn withdraw_specific_farm_tokens_test() {
         &rust_biguint!(second_farm_token_amount / 4),
         None,
     );
+
+    // positions are selected by both token identifier and nonce
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            let tokens_to_withdraw = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                2,
+                managed_biguint!(1),
+            ));
+
+            sc.withdraw_specific_farm_tokens_endpoint(tokens_to_withdraw, OptionalValue::None);
+        })
+        .assert_user_error("Invalid token to withdraw");
+}
+
+#[test]
+fn legacy_user_farm_tokens_migration_test() {
+    DebugApi::dummy();
+    let mut farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(2);
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let mut farms = Vec::new();
+    for farm_wrapper in &farm_setup.farm_wrappers {
+        farms.push(farm_wrapper.address_ref().clone());
+    }
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            for farm in &farms {
+                args.push(managed_address!(farm));
+            }
+            sc.add_farms(args);
+        })
+        .assert_ok();
+
+    // user enters both farms and deposits the farm tokens
+    let first_farm_token_amount = 100_000_000;
+    let second_farm_token_amount = 50_000_000;
+    let first_user = farm_setup.first_user.clone();
+    farm_setup.set_user_energy(&first_user, 1_000, 2, 1);
+    farm_setup.enter_farm(FIRST_FARM_INDEX, &first_user, first_farm_token_amount);
+    farm_setup.enter_farm(SECOND_FARM_INDEX, &first_user, second_farm_token_amount);
+
+    let farm_tokens = [
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[FIRST_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(first_farm_token_amount),
+        },
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[SECOND_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(second_farm_token_amount),
+        },
+    ];
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(&first_user, &auto_farm_wrapper, &farm_tokens, |sc| {
+            sc.deposit_farm_tokens(OptionalValue::None);
+        })
+        .assert_ok();
+
+    // move the positions back to the legacy layout, which had no deposit usage
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let all_tokens = sc.get_all_user_farm_tokens(user_id);
+            for farm_id in 1..=2 {
+                sc.user_farm_positions(user_id, farm_id).clear();
+            }
+            for farm in &farms {
+                sc.total_deposit_usage(&managed_address!(farm)).clear();
+                sc.user_deposit_usage(user_id, &managed_address!(farm))
+                    .clear();
+            }
+            sc.user_farm_ids(user_id).clear();
+            sc.user_farm_tokens(user_id).set(&all_tokens);
+        })
+        .assert_ok();
+
+    // legacy positions are still returned by the view
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            let mut expected_user_farm_tokens = ManagedVec::new();
+            expected_user_farm_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(first_farm_token_amount),
+            ));
+            expected_user_farm_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[SECOND_FARM_INDEX]),
+                1,
+                managed_biguint!(second_farm_token_amount),
+            ));
+            assert_eq!(
+                sc.get_user_farm_tokens_view(managed_address!(&first_user)),
+                expected_user_farm_tokens
+            );
+        })
+        .assert_ok();
+
+    // withdrawing migrates the user to the per-farm layout, and seeds the deposit usage
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            let tokens_to_withdraw = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(first_farm_token_amount),
+            ));
+            sc.withdraw_specific_farm_tokens_endpoint(tokens_to_withdraw, OptionalValue::None);
+
+            let user_id = 1;
+            assert!(sc.user_farm_tokens(user_id).is_empty());
+            assert!(!sc.user_farm_ids(user_id).contains(&1));
+            assert!(sc.user_farm_ids(user_id).contains(&2));
+            assert_eq!(
+                sc.user_farm_positions(user_id, 2).get(),
+                ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(FARM_TOKEN_ID[SECOND_FARM_INDEX]),
+                    1,
+                    managed_biguint!(second_farm_token_amount),
+                ))
+            );
+
+            let first_farm = managed_address!(&farms[FIRST_FARM_INDEX]);
+            let second_farm = managed_address!(&farms[SECOND_FARM_INDEX]);
+            assert_eq!(sc.user_deposit_usage(user_id, &first_farm).get(), 0);
+            assert_eq!(sc.total_deposit_usage(&first_farm).get(), 0);
+            assert_eq!(
+                sc.user_deposit_usage(user_id, &second_farm).get(),
+                managed_biguint!(second_farm_token_amount)
+            );
+            assert_eq!(
+                sc.total_deposit_usage(&second_farm).get(),
+                managed_biguint!(second_farm_token_amount)
+            );
+        })
+        .assert_ok();
+
+    farm_setup.b_mock.borrow_mut().check_nft_balance::<Empty>(
+        &first_user,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        1,
+        &rust_biguint!(first_farm_token_amount),
+        None,
+    );
+}
+
+#[test]
+fn deposit_farm_tokens_for_user_test() {
+    DebugApi::dummy();
+    let mut farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(2);
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let mut farms = Vec::new();
+    for farm_wrapper in &farm_setup.farm_wrappers {
+        farms.push(farm_wrapper.address_ref().clone());
+    }
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            for farm in &farms {
+                args.push(managed_address!(farm));
+            }
+            sc.add_farms(args);
+        })
+        .assert_ok();
+
+    // partner enters farm, and deposits the farm tokens for an unregistered user
+    let farm_token_amount = 100_000_000;
+    let partner = farm_setup.first_user.clone();
+    let beneficiary = farm_setup.second_user.clone();
+    farm_setup.set_user_energy(&partner, 1_000, 2, 1);
+    farm_setup.enter_farm(FIRST_FARM_INDEX, &partner, farm_token_amount);
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &partner,
+            &auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens_for(managed_address!(&beneficiary));
+
+                let expected_user_farm_tokens =
+                    ManagedVec::from_single_item(EsdtTokenPayment::new(
+                        managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                        1,
+                        managed_biguint!(farm_token_amount),
+                    ));
+                assert_eq!(
+                    sc.get_user_farm_tokens_view(managed_address!(&beneficiary)),
+                    expected_user_farm_tokens
+                );
+                assert!(sc
+                    .get_user_farm_tokens_view(managed_address!(&partner))
+                    .is_empty());
+            },
+        )
+        .assert_ok();
+
+    // partner cannot withdraw the beneficiary's tokens
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&partner, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_all_farm_tokens_endpoint(OptionalValue::Some(managed_address!(
+                &beneficiary
+            )));
+        })
+        .assert_user_error("Not a delegate of the user");
+
+    // beneficiary withdraws
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&beneficiary, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_all_farm_tokens_endpoint(OptionalValue::None);
+        })
+        .assert_ok();
+
+    farm_setup.b_mock.borrow_mut().check_nft_balance::<Empty>(
+        &beneficiary,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        1,
+        &rust_biguint!(farm_token_amount),
+        None,
+    );
 }
@@ -0,0 +1,302 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::RewardsWrapper;
+use auto_farm::deposit_caps::DepositCapsModule;
+use auto_farm::external_sc_interactions::farm_migration::FarmMigrationModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX, SECOND_FARM_INDEX};
+use config::ConfigModule;
+use farm::exit_penalty::ExitPenaltyModule;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use tests_common::farm_with_locked_rewards_setup::{
+    FARMING_TOKEN_ID, FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID,
+};
+
+const FARM_TOKEN_AMOUNT: u64 = 100_000_000;
+
+/// The first user deposits a position of the first farm, which is then replaced by the second farm.
+/// Both farms have the same farming token.
+fn setup_farm_migration<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>(
+    af_setup: &mut AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>,
+    min_output_percentage: u64,
+    penalty_percent: u64,
+) where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0);
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let old_farm_addr = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let new_farm_addr = af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.penalty_percent().set(penalty_percent);
+                sc.minimum_farming_epochs().set(10u64);
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.farming_token_id()
+                    .set(managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]));
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, FARM_TOKEN_AMOUNT);
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(FARM_TOKEN_AMOUNT),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut farms = MultiValueEncoded::new();
+            farms.push(managed_address!(&old_farm_addr));
+            sc.remove_farms(farms);
+
+            sc.add_farm_migration(
+                managed_address!(&old_farm_addr),
+                managed_address!(&new_farm_addr),
+                min_output_percentage,
+            );
+        })
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_nonce(10);
+    b_mock.borrow_mut().set_block_epoch(3);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 3, 1);
+}
+
+#[test]
+fn migrate_farm_position_test() {
+    DebugApi::dummy();
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    setup_farm_migration(&mut af_setup, 9_000, 0);
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let old_farm_addr = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let new_farm_addr = af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let tx_result = b_mock.borrow_mut().execute_tx(
+        &af_setup.proxy_address,
+        &af_setup.auto_farm_wrapper,
+        &rust_zero,
+        |sc| {
+            let user_id = 1;
+            let old_farm_id = 1;
+            let new_farm_id = 2;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.migrate_farm_positions(&managed_address!(&first_user), user_id, &mut rew_wrapper);
+
+            assert!(sc
+                .user_farm_positions(user_id, old_farm_id)
+                .get()
+                .is_empty());
+
+            let new_positions = sc.user_farm_positions(user_id, new_farm_id).get();
+            assert_eq!(new_positions.len(), 1);
+            let new_position = new_positions.get(0);
+            assert_eq!(
+                new_position.token_identifier,
+                managed_token_id!(FARM_TOKEN_ID[SECOND_FARM_INDEX])
+            );
+            assert_eq!(new_position.amount, managed_biguint!(FARM_TOKEN_AMOUNT));
+
+            let (users_count, migrated_amount) = sc
+                .get_farm_migration_progress(managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]))
+                .into_tuple();
+            assert_eq!(users_count, 1);
+            assert_eq!(migrated_amount, managed_biguint!(FARM_TOKEN_AMOUNT));
+
+            // the deposit usage moved from the old farm to the new farm
+            let old_farm_usage = sc
+                .user_deposit_usage(user_id, &managed_address!(&old_farm_addr))
+                .get();
+            assert_eq!(old_farm_usage, managed_biguint!(0));
+            let new_farm_usage = sc
+                .user_deposit_usage(user_id, &managed_address!(&new_farm_addr))
+                .get();
+            assert_eq!(new_farm_usage, managed_biguint!(FARM_TOKEN_AMOUNT));
+        },
+    );
+    tx_result.assert_ok();
+
+    let migration_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"farmMigration".to_vec())
+        .count();
+    assert_eq!(migration_event_logs, 1);
+}
+
+#[test]
+fn migration_output_below_minimum_test() {
+    DebugApi::dummy();
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    // 10% exit penalty, while at least 95% of the position is required
+    setup_farm_migration(&mut af_setup, 9_500, 1_000);
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let tx_result = b_mock.borrow_mut().execute_tx(
+        &af_setup.proxy_address,
+        &af_setup.auto_farm_wrapper,
+        &rust_zero,
+        |sc| {
+            let user_id = 1;
+            let old_farm_id = 1;
+            let new_farm_id = 2;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.migrate_farm_positions(&managed_address!(&first_user), user_id, &mut rew_wrapper);
+
+            // the position was exited, and the farming tokens were returned to the user's rewards
+            assert!(sc
+                .user_farm_positions(user_id, old_farm_id)
+                .get()
+                .is_empty());
+            assert!(sc.user_farm_positions(user_id, new_farm_id).is_empty());
+
+            let expected_farming_tokens = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]),
+                0,
+                managed_biguint!(FARM_TOKEN_AMOUNT * 9 / 10),
+            ));
+            assert_eq!(
+                sc.user_rewards(user_id).get().other_tokens.into_payments(),
+                expected_farming_tokens
+            );
+
+            let (users_count, migrated_amount) = sc
+                .get_farm_migration_progress(managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]))
+                .into_tuple();
+            assert_eq!(users_count, 0);
+            assert_eq!(migrated_amount, managed_biguint!(0));
+        },
+    );
+    tx_result.assert_ok();
+
+    let failed_migration_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"farmMigrationFailed".to_vec())
+        .count();
+    assert_eq!(failed_migration_event_logs, 1);
+}
+
+#[test]
+fn remove_farm_keeps_other_farm_farming_token_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_farm_addr = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let second_farm_addr = af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    // the second farm now uses the farming token associated with the first farm
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[SECOND_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.farming_token_id()
+                    .set(managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]));
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut farms = MultiValueEncoded::new();
+            farms.push(managed_address!(&second_farm_addr));
+            sc.remove_farms(farms);
+
+            assert_eq!(
+                sc.get_farm_for_farming_token_view(managed_token_id!(
+                    FARMING_TOKEN_ID[FIRST_FARM_INDEX]
+                ))
+                .into_option(),
+                Some(managed_address!(&first_farm_addr))
+            );
+            assert!(sc
+                .farm_for_farm_token(&managed_token_id!(FARM_TOKEN_ID[SECOND_FARM_INDEX]))
+                .is_empty());
+        })
+        .assert_ok();
+}
@@ -0,0 +1,216 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::external_sc_interactions::keeper_compensation::KeeperCompensationModule;
+use auto_farm::external_sc_interactions::position_creation::PositionCreationModule;
+use auto_farm::fee_tiers::FeeTiersModule;
+use auto_farm::fees::FeesModule;
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::types::{MultiValue2, MultiValueEncoded};
+use multiversx_sc_scenario::{managed_address, managed_biguint, managed_token_id, rust_biguint};
+use tests_common::farm_with_locked_rewards_setup::LOCKED_REWARD_TOKEN_ID;
+
+#[test]
+fn fee_tiers_test() {
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        mut farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user.clone();
+    let second_user = farm_setup.second_user.clone();
+    let third_user = farm_setup.third_user.clone();
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(2);
+    farm_setup.set_user_energy(&first_user, 1_000, 2, 1);
+    farm_setup.set_user_energy(&second_user, 5_000, 2, 1);
+    farm_setup.set_user_energy(&third_user, 100, 2, 1);
+
+    // try set unsorted tiers
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push(MultiValue2::from((managed_biguint!(2_000), 200)));
+            tiers.push(MultiValue2::from((managed_biguint!(500), 500)));
+            sc.set_fee_tiers(tiers);
+        })
+        .assert_user_error("Fee tiers not sorted");
+
+    // set tiers
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push(MultiValue2::from((managed_biguint!(500), 500)));
+            tiers.push(MultiValue2::from((managed_biguint!(2_000), 200)));
+            sc.set_fee_tiers(tiers);
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_effective_fee_percentage_view(managed_address!(&first_user)),
+                500
+            );
+            assert_eq!(
+                sc.get_effective_fee_percentage_view(managed_address!(&second_user)),
+                200
+            );
+            assert_eq!(
+                sc.get_effective_fee_percentage_view(managed_address!(&third_user)),
+                FEE_PERCENTAGE
+            );
+        })
+        .assert_ok();
+
+    // user override
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_user_fee_override(managed_address!(&third_user), 0);
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            assert_eq!(
+                sc.get_effective_fee_percentage_view(managed_address!(&third_user)),
+                0
+            );
+        })
+        .assert_ok();
+
+    // remove override and change base fee
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.remove_user_fee_override(managed_address!(&third_user));
+            sc.set_fee_percentage(800);
+
+            assert_eq!(
+                sc.get_effective_fee_percentage_view(managed_address!(&third_user)),
+                800
+            );
+        })
+        .assert_ok();
+
+    // only owner can change fees
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_fee_percentage(0);
+        })
+        .assert_user_error("Endpoint can only be called by owner");
+}
+
+#[test]
+fn keeper_compensation_config_test() {
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+
+    // only used as smart contract addresses, the farm is not a valid pair
+    let router_addr = farm_setup.farm_wrappers[0].address_ref().clone();
+    let pair_addr = farm_setup.farm_wrappers[1].address_ref().clone();
+
+    // the compensation config is required before setting pairs
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_keeper_compensation_pair(
+                managed_token_id!(b"MEX-123456"),
+                managed_address!(&pair_addr),
+            );
+        })
+        .assert_user_error("Compensation config not set");
+
+    // router and EGLD wrapper addresses are required
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_keeper_compensation_config(1_000, managed_token_id!(b"WEGLD-123456"), 500);
+        })
+        .assert_user_error("Router and EGLD wrapper addresses not set");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_position_creation_addresses(
+                managed_address!(&energy_factory_addr),
+                managed_address!(&router_addr),
+            );
+            sc.set_keeper_compensation_config(1_000, managed_token_id!(b"WEGLD-123456"), 500);
+
+            assert_eq!(sc.keeper_compensation_share().get(), 1_000);
+            assert_eq!(sc.keeper_compensation_max_slippage().get(), 500);
+        })
+        .assert_ok();
+
+    // locked tokens cannot be converted
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_keeper_compensation_pair(
+                managed_token_id!(LOCKED_REWARD_TOKEN_ID),
+                managed_address!(&pair_addr),
+            );
+        })
+        .assert_user_error("Locked tokens cannot be converted");
+
+    // the pair must be between the token and WEGLD
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_keeper_compensation_pair(
+                managed_token_id!(b"MEX-123456"),
+                managed_address!(&pair_addr),
+            );
+        })
+        .assert_user_error("Invalid compensation pair");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            assert!(sc
+                .get_keeper_compensation_pair_view(managed_token_id!(b"MEX-123456"))
+                .into_option()
+                .is_none());
+            assert!(sc
+                .get_pending_keeper_compensation_view(managed_address!(&farm_setup.owner))
+                .is_empty());
+        })
+        .assert_ok();
+}
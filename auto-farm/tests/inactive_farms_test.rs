@@ -0,0 +1,292 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::RewardsWrapper;
+use auto_farm::external_sc_interactions::farm_actions::FarmActionsModule;
+use auto_farm::inactive_farms::{InactiveFarmPolicy, InactiveFarmsModule};
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX};
+use farm::exit_penalty::ExitPenaltyModule;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{Address, EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use pausable::{PausableModule, State};
+use tests_common::farm_staking_setup::setup_farm_staking;
+use tests_common::farm_with_locked_rewards_setup::{
+    FARMING_TOKEN_ID, FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID,
+};
+
+static STAKING_TOKEN_ID: &[u8] = b"STAKE-123456";
+const FARM_TOKEN_AMOUNT: u64 = 100_000_000;
+
+/// All three users deposit a position of the first farm, which is then paused
+fn setup_inactive_farm<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>(
+    af_setup: &mut AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>,
+) where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0);
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let users = [
+        af_setup.farm_setup.first_user.clone(),
+        af_setup.farm_setup.second_user.clone(),
+        af_setup.farm_setup.third_user.clone(),
+    ];
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.penalty_percent().set(0u64);
+                sc.minimum_farming_epochs().set(0u64);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    for (i, user) in users.iter().enumerate() {
+        af_setup
+            .farm_setup
+            .enter_farm(FIRST_FARM_INDEX, user, FARM_TOKEN_AMOUNT);
+
+        b_mock
+            .borrow_mut()
+            .execute_esdt_transfer(
+                user,
+                &af_setup.auto_farm_wrapper,
+                FARM_TOKEN_ID[FIRST_FARM_INDEX],
+                i as u64 + 1,
+                &rust_biguint!(FARM_TOKEN_AMOUNT),
+                |sc| {
+                    sc.deposit_farm_tokens(OptionalValue::None);
+                },
+            )
+            .assert_ok();
+    }
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.state().set(State::Inactive);
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn set_inactive_farm_policy_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    let fs_wrapper = setup_farm_staking(
+        &mut b_mock.borrow_mut(),
+        farm_staking::contract_obj,
+        STAKING_TOKEN_ID,
+        STAKING_TOKEN_ID,
+    );
+    let fs_addr = fs_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut farms = MultiValueEncoded::new();
+            farms.push(managed_address!(&fs_addr));
+            sc.add_farms(farms);
+        })
+        .assert_ok();
+
+    // staking farms have no exitFarm endpoint
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_inactive_farm_policy(managed_address!(&fs_addr), InactiveFarmPolicy::Exit);
+        })
+        .assert_user_error("Exit policy not supported for staking farms");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_inactive_farm_policy(managed_address!(&fs_addr), InactiveFarmPolicy::Notify);
+            sc.set_inactive_farm_policy(managed_address!(&first_farm), InactiveFarmPolicy::Exit);
+
+            assert_eq!(
+                sc.get_inactive_farm_policy_view(managed_address!(&fs_addr)),
+                InactiveFarmPolicy::Notify
+            );
+            assert_eq!(
+                sc.get_inactive_farm_policy_view(managed_address!(&first_farm)),
+                InactiveFarmPolicy::Exit
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn get_users_in_inactive_farms_test() {
+    DebugApi::dummy();
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    setup_inactive_farm(&mut af_setup);
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let users: Vec<Address> = vec![
+        af_setup.farm_setup.first_user.clone(),
+        af_setup.farm_setup.second_user.clone(),
+        af_setup.farm_setup.third_user.clone(),
+    ];
+    b_mock
+        .borrow_mut()
+        .execute_query(&af_setup.auto_farm_wrapper, |sc| {
+            let first_page = sc.get_users_in_inactive_farms(1, 2).to_vec();
+            assert_eq!(first_page.len(), 2);
+            assert_eq!(*first_page.get(0), managed_address!(&users[0]));
+            assert_eq!(*first_page.get(1), managed_address!(&users[1]));
+
+            // the count is capped to the last user ID
+            let second_page = sc.get_users_in_inactive_farms(3, 10).to_vec();
+            assert_eq!(second_page.len(), 1);
+            assert_eq!(*second_page.get(0), managed_address!(&users[2]));
+
+            assert!(sc.get_users_in_inactive_farms(4, 10).is_empty());
+            assert!(sc.get_users_in_inactive_farms(1, 0).is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn inactive_farm_exit_policy_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    setup_inactive_farm(&mut af_setup);
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let second_user = af_setup.farm_setup.second_user.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    // the default policy keeps the position
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &af_setup.proxy_address,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let user_id = 1;
+                let farm_id = 1;
+                let mut rew_wrapper =
+                    RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+                sc.claim_all_farm_rewards(
+                    &managed_address!(&first_user),
+                    user_id,
+                    &mut rew_wrapper,
+                );
+
+                let expected_positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                    1,
+                    managed_biguint!(FARM_TOKEN_AMOUNT),
+                ));
+                assert_eq!(
+                    sc.user_farm_positions(user_id, farm_id).get(),
+                    expected_positions
+                );
+            },
+        )
+        .assert_ok();
+
+    // notify keeps the position, and emits an event for it
+    let tx_result =
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                sc.set_inactive_farm_policy(
+                    managed_address!(&first_farm),
+                    InactiveFarmPolicy::Notify,
+                );
+
+                let user_id = 1;
+                let farm_id = 1;
+                let mut rew_wrapper =
+                    RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+                sc.claim_all_farm_rewards(
+                    &managed_address!(&first_user),
+                    user_id,
+                    &mut rew_wrapper,
+                );
+
+                assert_eq!(sc.user_farm_positions(user_id, farm_id).get().len(), 1);
+            });
+    tx_result.assert_ok();
+
+    let notify_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"inactiveFarmPosition".to_vec())
+        .count();
+    assert_eq!(notify_event_logs, 1);
+
+    // exit returns the farming tokens to the user's rewards
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_inactive_farm_policy(managed_address!(&first_farm), InactiveFarmPolicy::Exit);
+
+            let user_id = 2;
+            let farm_id = 1;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.claim_all_farm_rewards(&managed_address!(&second_user), user_id, &mut rew_wrapper);
+
+            assert!(sc.user_farm_positions(user_id, farm_id).get().is_empty());
+
+            let expected_farming_tokens = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]),
+                0,
+                managed_biguint!(FARM_TOKEN_AMOUNT),
+            ));
+            assert_eq!(
+                sc.user_rewards(user_id).get().other_tokens.into_payments(),
+                expected_farming_tokens
+            );
+        })
+        .assert_ok();
+}
@@ -0,0 +1,282 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod fees_collector_setup;
+pub mod metastaking_setup;
+pub mod pair_setup;
+pub mod router_setup;
+
+use auto_farm::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule;
+use auto_farm::external_sc_interactions::keeper_compensation::KeeperCompensationModule;
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::external_sc_interactions::position_creation::PositionCreationModule;
+use auto_farm::fees::FeesModule;
+use auto_farm::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule;
+use auto_farm::user_tokens::user_strategy::{CompoundStrategy, UserStrategyModule};
+use fees_collector_setup::setup_fees_collector;
+use metastaking_setup::{MetastakingSetup, DUAL_YIELD_TOKEN_ID, TOKEN_IDS};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtLocalRole, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use multiversx_wegld_swap_sc::EgldEsdtSwap;
+use num_traits::ToPrimitive;
+use pair::safe_price::SafePriceModule;
+use pair_setup::PairSetup;
+use router_setup::RouterSetup;
+use sc_whitelist_module::SCWhitelistModule;
+
+static WEGLD_TOKEN_ID: &[u8] = b"WEGLD-123456";
+static WEGLD_LP_TOKEN_ID: &[u8] = b"LPWEGLD-123456";
+
+const COMPENSATION_SHARE: u64 = 5_000; // 50%
+const MAX_SLIPPAGE: u64 = 500; // 5%
+
+#[test]
+fn keeper_compensation_conversion_test() {
+    DebugApi::dummy();
+    let mut ms_setup = MetastakingSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+        pair::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = ms_setup.af_setup.farm_setup.b_mock.clone();
+    let owner = ms_setup.af_setup.farm_setup.owner.clone();
+    let first_user = ms_setup.af_setup.farm_setup.first_user.clone();
+    let second_user = ms_setup.af_setup.farm_setup.second_user.clone();
+    let third_user = ms_setup.af_setup.farm_setup.third_user.clone();
+    let keeper = ms_setup.af_setup.proxy_address.clone();
+    let energy_factory_addr = ms_setup
+        .af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = ms_setup.af_setup.auto_farm_wrapper.address_ref().clone();
+
+    // pair (A, WEGLD), router and EGLD wrapper used for the conversion
+    let mut wegld_pair_setup = PairSetup::new(
+        b_mock.clone(),
+        pair::contract_obj,
+        &owner,
+        None,
+        TOKEN_IDS[0],
+        WEGLD_TOKEN_ID,
+        WEGLD_LP_TOKEN_ID,
+    );
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[0], &rust_biguint!(1_000_000_000));
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, WEGLD_TOKEN_ID, &rust_biguint!(1_000_000_000));
+    wegld_pair_setup.add_liquidity(&owner, 1_000_000_000, 1_000_000_000);
+
+    // setup price observations, so the pair has a safe price
+    // the metastaking setup already used the rounds up to 21
+    let mut block_round: u64 = 21;
+    for _i in 1usize..=20 {
+        block_round += 1;
+        b_mock.borrow_mut().set_block_round(block_round);
+
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &wegld_pair_setup.pair_wrapper, &rust_zero, |sc| {
+                sc.update_safe_price(
+                    &managed_biguint!(1_000_000_000),
+                    &managed_biguint!(1_000_000_000),
+                    &managed_biguint!(1_000_000_000),
+                );
+            })
+            .assert_ok();
+    }
+
+    let mut router_setup = RouterSetup::new(
+        b_mock.clone(),
+        router::contract_obj,
+        &owner,
+        wegld_pair_setup.pair_wrapper.address_ref(),
+    );
+    router_setup.whitelist_pair(
+        &owner,
+        TOKEN_IDS[0],
+        WEGLD_TOKEN_ID,
+        wegld_pair_setup.pair_wrapper.address_ref(),
+    );
+
+    let egld_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_biguint!(1_000_000_000),
+        Some(&owner),
+        multiversx_wegld_swap_sc::contract_obj,
+        "egld wrapper",
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &egld_wrapper, &rust_zero, |sc| {
+            sc.init(managed_token_id!(WEGLD_TOKEN_ID));
+        })
+        .assert_ok();
+    b_mock.borrow_mut().set_esdt_local_roles(
+        egld_wrapper.address_ref(),
+        WEGLD_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+
+    // fees collector claimed in the batch
+    let fc_wrapper = setup_fees_collector(
+        &mut b_mock.borrow_mut(),
+        fees_collector::contract_obj,
+        &energy_factory_addr,
+        &first_user,
+        &second_user,
+        &third_user,
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fc_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+
+    let pair_addr = wegld_pair_setup.pair_wrapper.address_ref().clone();
+    let router_addr = router_setup.router_wrapper.address_ref().clone();
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, auto_farm_wrapper, &rust_zero, |sc| {
+            sc.fees_collector_sc_address()
+                .set(managed_address!(fc_wrapper.address_ref()));
+            sc.set_position_creation_addresses(
+                managed_address!(egld_wrapper.address_ref()),
+                managed_address!(&router_addr),
+            );
+            sc.set_keeper_compensation_config(
+                COMPENSATION_SHARE,
+                managed_token_id!(WEGLD_TOKEN_ID),
+                MAX_SLIPPAGE,
+            );
+            sc.set_keeper_compensation_pair(
+                managed_token_id!(TOKEN_IDS[0]),
+                managed_address!(&pair_addr),
+            );
+        })
+        .assert_ok();
+
+    // user enters metastaking and deposits the dual yield tokens
+    b_mock.borrow_mut().set_block_epoch(2);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    let dual_yield_amount = 100_000_000;
+    let dual_yield_nonce = ms_setup.enter_metastaking(&first_user, 1, dual_yield_amount);
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_nonce,
+            &rust_biguint!(dual_yield_amount),
+            |sc| {
+                sc.deposit_metastaking_tokens(OptionalValue::None);
+                sc.set_compound_strategy(CompoundStrategy::ClaimOnly, OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    // advance blocks to generate staking rewards, which are taken as fees in token A
+    b_mock.borrow_mut().set_block_nonce(1_000);
+    b_mock.borrow_mut().set_block_epoch(6);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 6, 1);
+
+    // the price of token A is pushed below the safe price, so the conversion is postponed
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[0], &rust_biguint!(1_000_000_000));
+    let (_, wegld_amount_out) =
+        wegld_pair_setup.swap_fixed_input(&owner, TOKEN_IDS[0], 1_000_000_000);
+
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, auto_farm_wrapper, &rust_zero, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+
+            assert!(!sc
+                .get_pending_keeper_compensation_view(managed_address!(&keeper))
+                .is_empty());
+        })
+        .assert_ok();
+    b_mock.borrow().check_egld_balance(&keeper, &rust_zero);
+
+    // the price is restored
+    let _ = wegld_pair_setup.swap_fixed_input(
+        &owner,
+        WEGLD_TOKEN_ID,
+        wegld_amount_out.to_u64().unwrap(),
+    );
+    b_mock.borrow_mut().set_block_nonce(1_100);
+
+    // the batch converts the compensation share of the fees and sends EGLD to the keeper
+    let tx_result = b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, auto_farm_wrapper, &rust_zero, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+        });
+    tx_result.assert_ok();
+
+    let keeper_egld_balance = b_mock.borrow().get_egld_balance(&keeper);
+    assert!(keeper_egld_balance > rust_biguint!(0));
+
+    b_mock
+        .borrow_mut()
+        .execute_query(auto_farm_wrapper, |sc| {
+            assert!(sc
+                .get_pending_keeper_compensation_view(managed_address!(&keeper))
+                .is_empty());
+
+            let total_compensation = sc
+                .keeper_egld_compensation(&managed_address!(&keeper))
+                .get();
+            assert_eq!(
+                num_bigint::BigUint::from_bytes_be(total_compensation.to_bytes_be().as_slice()),
+                keeper_egld_balance
+            );
+        })
+        .assert_ok();
+
+    let compensation_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"keeperCompensation".to_vec())
+        .count();
+    assert_eq!(compensation_event_logs, 1);
+
+    // an empty batch does not convert anything
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, auto_farm_wrapper, &rust_zero, |sc| {
+            sc.claim_all_rewards_and_compound(MultiValueEncoded::new());
+        })
+        .assert_ok();
+    b_mock
+        .borrow()
+        .check_egld_balance(&keeper, &keeper_egld_balance);
+}
@@ -0,0 +1,199 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::common_storage::CommonStorageModule;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::fees::FeesModule;
+use auto_farm::whitelists::keepers_whitelist::KeepersWhitelistModule;
+use auto_farm::AutoFarm;
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+
+static REWARD_TOKEN_ID: &[u8] = b"REW-123456";
+
+#[test]
+fn keepers_whitelist_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_keeper = af_setup.proxy_address.clone();
+    let second_keeper = af_setup.farm_setup.second_user.clone();
+    let new_treasury = af_setup.farm_setup.third_user.clone();
+
+    // the proxy claim address given at init is the first keeper and the treasury
+    b_mock
+        .borrow_mut()
+        .execute_query(&af_setup.auto_farm_wrapper, |sc| {
+            assert!(sc.keepers().contains(&managed_address!(&first_keeper)));
+            assert_eq!(sc.treasury_address().get(), managed_address!(&first_keeper));
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &second_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.claim_all_rewards_and_compound(MultiValueEncoded::new());
+            },
+        )
+        .assert_user_error("Only keepers can claim in user's place");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &second_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let mut keepers = MultiValueEncoded::new();
+                keepers.push(managed_address!(&second_keeper));
+                sc.add_keepers(keepers);
+            },
+        )
+        .assert_user_error("Endpoint can only be called by owner");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut keepers = MultiValueEncoded::new();
+            keepers.push(managed_address!(&second_keeper));
+            sc.add_keepers(keepers);
+        })
+        .assert_ok();
+
+    // both keepers can process users
+    for keeper in [&first_keeper, &second_keeper] {
+        b_mock
+            .borrow_mut()
+            .execute_tx(keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                sc.claim_all_rewards_and_compound(MultiValueEncoded::new());
+            })
+            .assert_ok();
+    }
+
+    // only the treasury can claim the fees
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &second_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let _ = sc.claim_fees();
+            },
+        )
+        .assert_user_error("Only the treasury can claim fees");
+
+    // the first keeper has compensation waiting to be converted
+    let pending_amount = 1_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        af_setup.auto_farm_wrapper.address_ref(),
+        REWARD_TOKEN_ID,
+        &rust_biguint!(pending_amount),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.pending_keeper_compensation(&managed_address!(&first_keeper))
+                .set(UniquePayments::new_from_unique_payments(
+                    ManagedVec::from_single_item(EsdtTokenPayment::new(
+                        managed_token_id!(REWARD_TOKEN_ID),
+                        0,
+                        managed_biguint!(pending_amount),
+                    )),
+                ));
+        })
+        .assert_ok();
+
+    // the removed keeper's pending compensation is moved to the treasury
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.change_treasury_address(managed_address!(&new_treasury));
+
+            let mut keepers = MultiValueEncoded::new();
+            keepers.push(managed_address!(&first_keeper));
+            sc.remove_keepers(keepers);
+
+            assert!(sc
+                .pending_keeper_compensation(&managed_address!(&first_keeper))
+                .is_empty());
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &first_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let _ = sc.claim_fees();
+            },
+        )
+        .assert_user_error("Only the treasury can claim fees");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &new_treasury,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                let fees = sc.claim_fees();
+                assert_eq!(
+                    fees,
+                    ManagedVec::from_single_item(EsdtTokenPayment::new(
+                        managed_token_id!(REWARD_TOKEN_ID),
+                        0,
+                        managed_biguint!(pending_amount),
+                    ))
+                );
+            },
+        )
+        .assert_ok();
+    b_mock.borrow().check_esdt_balance(
+        &new_treasury,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(pending_amount),
+    );
+
+    // the removed keeper can no longer process users
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &first_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.claim_all_rewards_and_compound(MultiValueEncoded::new());
+            },
+        )
+        .assert_user_error("Only keepers can claim in user's place");
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &second_keeper,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.claim_all_rewards_and_compound(MultiValueEncoded::new());
+            },
+        )
+        .assert_ok();
+}
@@ -0,0 +1,337 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod fees_collector_setup;
+pub mod pair_setup;
+
+use auto_farm::common::rewards_wrapper::MergedRewardsWrapper;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule;
+use auto_farm::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule;
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::user_tokens::user_strategy::{CompoundStrategy, UserStrategyModule};
+use auto_farm_setup::AutoFarmSetup;
+use farm::exit_penalty::ExitPenaltyModule;
+use fees_collector_setup::setup_fees_collector;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use pair::safe_price::SafePriceModule;
+use pair_setup::PairSetup;
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::{FARMING_TOKEN_ID, FARM_TOKEN_ID};
+
+const LP_FARM_INDEX: usize = 0;
+
+static TOKEN_IDS: &[&[u8]] = &[b"TOKENA-123456", b"TOKENB-123456"];
+
+#[test]
+fn compound_into_existing_lp_farm_position_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let keeper = af_setup.proxy_address.clone();
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+
+    let fc_wrapper = setup_fees_collector(
+        &mut b_mock.borrow_mut(),
+        fees_collector::contract_obj,
+        &energy_factory_addr,
+        &first_user,
+        &af_setup.farm_setup.second_user,
+        &af_setup.farm_setup.third_user,
+    );
+    let fc_addr = fc_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fc_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.fees_collector_sc_address()
+                .set(managed_address!(&fc_addr));
+        })
+        .assert_ok();
+
+    // the LP farm's farming token is the LP token of the pair
+    let mut pair_setup = PairSetup::new(
+        b_mock.clone(),
+        pair::contract_obj,
+        &owner,
+        None,
+        TOKEN_IDS[0],
+        TOKEN_IDS[1],
+        FARMING_TOKEN_ID[LP_FARM_INDEX],
+    );
+    let pair_addr = pair_setup.pair_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[0], &rust_biguint!(1_000_000_000));
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[1], &rust_biguint!(2_000_000_000));
+
+    let mut block_round: u64 = 1;
+    b_mock.borrow_mut().set_block_round(block_round);
+    pair_setup.add_liquidity(&owner, 1_000_000_000, 2_000_000_000);
+
+    // setup price observations, so the pair has a safe price
+    for _i in 1usize..=20 {
+        block_round += 1;
+        b_mock.borrow_mut().set_block_round(block_round);
+
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &pair_setup.pair_wrapper, &rust_zero, |sc| {
+                sc.update_safe_price(
+                    &managed_biguint!(1_000_000_000),
+                    &managed_biguint!(2_000_000_000),
+                    &managed_biguint!(1_000_000_000),
+                );
+            })
+            .assert_ok();
+    }
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[LP_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.pair_contract_address().set(managed_address!(&pair_addr));
+                sc.penalty_percent().set(0u64);
+            },
+        )
+        .assert_ok();
+
+    // user enters the LP farm, deposits the farm tokens, and chooses LP farm compounding
+    b_mock.borrow_mut().set_block_epoch(2);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    let farm_token_amount = 100_000_000;
+    af_setup
+        .farm_setup
+        .enter_farm(LP_FARM_INDEX, &first_user, farm_token_amount);
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[LP_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(CompoundStrategy::CompoundIntoLpFarm, OptionalValue::None);
+        })
+        .assert_ok();
+
+    // only the owner sets the max slippage
+    for caller in [&first_user, &keeper] {
+        b_mock
+            .borrow_mut()
+            .execute_tx(caller, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                sc.set_lp_compound_max_slippage(500);
+            })
+            .assert_user_error("Endpoint can only be called by owner");
+    }
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_lp_compound_max_slippage(10_000);
+        })
+        .assert_user_error("Invalid slippage");
+
+    // the user has token A rewards, held by auto-farm
+    let compound_amount = 1_000_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        &auto_farm_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(compound_amount),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            set_user_token_rewards(&sc, TOKEN_IDS[0], compound_amount);
+        })
+        .assert_ok();
+
+    // advance blocks, so the farm position has rewards
+    b_mock.borrow_mut().set_block_nonce(10);
+
+    // LP farm compounding is disabled until the max slippage is set
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+
+            let (user_id, farm_id) = (1, 1);
+            let farm_tokens = sc.user_farm_positions(user_id, farm_id).get();
+            assert_eq!(farm_tokens.len(), 1);
+            assert_eq!(
+                farm_tokens.get(0).amount,
+                managed_biguint!(farm_token_amount)
+            );
+            assert_eq!(
+                sc.user_rewards(user_id).get().other_tokens.into_payments(),
+                ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(compound_amount),
+                ))
+            );
+        })
+        .assert_ok();
+
+    // half of the rewards are swapped, and the LP tokens are added to the existing position
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_lp_compound_max_slippage(500);
+        })
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_nonce(20);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+
+            let (user_id, farm_id) = (1, 1);
+            let farm_tokens = sc.user_farm_positions(user_id, farm_id).get();
+            assert_eq!(farm_tokens.len(), 1);
+            let new_position = farm_tokens.get(0);
+            assert_eq!(
+                new_position.token_identifier,
+                managed_token_id!(FARM_TOKEN_ID[LP_FARM_INDEX])
+            );
+            assert!(new_position.amount > managed_biguint!(farm_token_amount));
+
+            // only the add liquidity leftovers are kept as rewards
+            for leftover in sc
+                .user_rewards(user_id)
+                .get()
+                .other_tokens
+                .into_payments()
+                .iter()
+            {
+                assert!(leftover.amount < managed_biguint!(compound_amount / 100));
+            }
+        })
+        .assert_ok();
+
+    // the price of token A is pushed below the safe price, so the swap minimum is not met,
+    // and the rewards are kept, without failing the batch
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[0], &rust_biguint!(1_000_000_000));
+    let _ = pair_setup.swap_fixed_input(&owner, TOKEN_IDS[0], 1_000_000_000);
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &auto_farm_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(compound_amount),
+    );
+    b_mock.borrow_mut().set_block_nonce(30);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            set_user_token_rewards(&sc, TOKEN_IDS[0], compound_amount);
+
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+
+            let user_id = 1;
+            assert_eq!(
+                sc.user_rewards(user_id).get().other_tokens.into_payments(),
+                ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(compound_amount),
+                ))
+            );
+        })
+        .assert_ok();
+
+    // the price of token A is pushed far above the safe price, so the swap minimum is met,
+    // but the price impact of the swap is too high to add liquidity at the minimum amounts
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&owner, TOKEN_IDS[1], &rust_biguint!(3_000_000_000u64));
+    let _ = pair_setup.swap_fixed_input(&owner, TOKEN_IDS[1], 3_000_000_000u64);
+
+    let large_compound_amount = 100_000_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        &auto_farm_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(large_compound_amount),
+    );
+    b_mock.borrow_mut().set_block_nonce(40);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            set_user_token_rewards(&sc, TOKEN_IDS[0], large_compound_amount);
+
+            let mut users = MultiValueEncoded::new();
+            users.push(managed_address!(&first_user));
+            sc.claim_all_rewards_and_compound(users);
+
+            let user_id = 1;
+            assert_eq!(
+                sc.user_rewards(user_id).get().other_tokens.into_payments(),
+                ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(large_compound_amount),
+                ))
+            );
+        })
+        .assert_ok();
+}
+
+fn set_user_token_rewards(sc: &auto_farm::ContractObj<DebugApi>, token_id: &[u8], amount: u64) {
+    let user_id = 1;
+    sc.user_rewards(user_id).set(MergedRewardsWrapper {
+        opt_locked_tokens: None,
+        other_tokens: UniquePayments::new_from_unique_payments(ManagedVec::from_single_item(
+            EsdtTokenPayment::new(managed_token_id!(token_id), 0, managed_biguint!(amount)),
+        )),
+    });
+}
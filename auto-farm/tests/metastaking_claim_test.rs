@@ -0,0 +1,297 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod metastaking_setup;
+pub mod pair_setup;
+
+use auto_farm::common::rewards_wrapper::{MergedRewardsWrapper, RewardsWrapper};
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::deposit_caps::DepositCapsModule;
+use auto_farm::external_sc_interactions::lp_farm_compounding::LpFarmCompoundingModule;
+use auto_farm::external_sc_interactions::metastaking_actions::MetastakingActionsModule;
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::user_tokens::user_strategy::CompoundStrategy;
+use metastaking_setup::{MetastakingSetup, DUAL_YIELD_TOKEN_ID, TOKEN_IDS};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::codec::Empty;
+use multiversx_sc::storage::mappers::{SingleValueMapper, StorageMapper};
+use multiversx_sc::storage::StorageKey;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use tests_common::farm_with_locked_rewards_setup::LOCKED_REWARD_TOKEN_ID;
+
+#[test]
+fn legacy_user_metastaking_tokens_migration_test() {
+    DebugApi::dummy();
+    let mut ms_setup = MetastakingSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+        pair::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = ms_setup.af_setup.farm_setup.b_mock.clone();
+    let owner = ms_setup.af_setup.farm_setup.owner.clone();
+    let first_user = ms_setup.af_setup.farm_setup.first_user.clone();
+    let proxy_address = ms_setup.af_setup.proxy_address.clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    // user enters metastaking and deposits the dual yield tokens
+    let dual_yield_amount = 100_000_000;
+    let dual_yield_nonce = ms_setup.enter_metastaking(&first_user, 1, dual_yield_amount);
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_nonce,
+            &rust_biguint!(dual_yield_amount),
+            |sc| {
+                sc.deposit_metastaking_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    // move the position back under the legacy storage key, which had no deposit usage
+    let ms_addr = ms_setup.ms_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let ms_id = 1;
+            let all_tokens = sc.get_all_user_metastaking_tokens(user_id);
+            sc.user_metastaking_positions(user_id, ms_id).clear();
+            sc.user_metastaking_ids(user_id).clear();
+            sc.total_deposit_usage(&managed_address!(&ms_addr)).clear();
+            sc.user_deposit_usage(user_id, &managed_address!(&ms_addr))
+                .clear();
+
+            let mut legacy_key = StorageKey::new(b"userMSTokens");
+            legacy_key.append_item(&user_id);
+            SingleValueMapper::<DebugApi, ManagedVec<DebugApi, EsdtTokenPayment<DebugApi>>>::new(
+                legacy_key,
+            )
+            .set(&all_tokens);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(auto_farm_wrapper, |sc| {
+            let expected_tokens = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(DUAL_YIELD_TOKEN_ID),
+                dual_yield_nonce,
+                managed_biguint!(dual_yield_amount),
+            ));
+            assert_eq!(
+                sc.get_user_metastaking_tokens_view(managed_address!(&first_user)),
+                expected_tokens
+            );
+        })
+        .assert_ok();
+
+    // advance blocks and epochs to generate rewards
+    b_mock.borrow_mut().set_block_nonce(10);
+    b_mock.borrow_mut().set_block_epoch(6);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 6, 1);
+
+    // keeper claim migrates the legacy position, seeds its deposit usage, and claims it
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.migrate_legacy_user_metastaking_tokens(user_id);
+            sc.claim_all_metastaking_rewards(
+                &managed_address!(&first_user),
+                user_id,
+                &mut rew_wrapper,
+            );
+            sc.add_user_rewards(managed_address!(&first_user), user_id, rew_wrapper);
+
+            assert!(sc.user_metastaking_tokens(user_id).is_empty());
+            assert!(sc.user_metastaking_ids(user_id).contains(&1));
+
+            let usage = sc
+                .user_deposit_usage(user_id, &managed_address!(&ms_addr))
+                .get();
+            assert!(usage > 0);
+            assert_eq!(
+                sc.total_deposit_usage(&managed_address!(&ms_addr)).get(),
+                usage
+            );
+
+            let positions = sc.user_metastaking_positions(user_id, 1).get();
+            assert_eq!(positions.len(), 1);
+            let new_position = positions.get(0);
+            assert_eq!(
+                new_position.token_identifier,
+                managed_token_id!(DUAL_YIELD_TOKEN_ID)
+            );
+            assert!(new_position.token_nonce > dual_yield_nonce);
+            assert_eq!(new_position.amount, managed_biguint!(dual_yield_amount));
+
+            assert!(!sc.user_rewards(user_id).is_empty());
+        })
+        .assert_ok();
+
+    // move the claimed position back to the legacy layout, then withdraw it
+    let mut new_dual_yield_nonce = 0;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let all_tokens = sc.get_all_user_metastaking_tokens(user_id);
+            new_dual_yield_nonce = all_tokens.get(0).token_nonce;
+            sc.user_metastaking_positions(user_id, 1).clear();
+            sc.user_metastaking_ids(user_id).clear();
+            sc.user_metastaking_tokens(user_id).set(&all_tokens);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, auto_farm_wrapper, &rust_zero, |sc| {
+            let withdrawn_tokens = sc.withdraw_all_metastaking_tokens_endpoint(OptionalValue::None);
+            assert_eq!(withdrawn_tokens.len(), 1);
+            assert!(sc.user_metastaking_tokens(1).is_empty());
+        })
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user,
+        DUAL_YIELD_TOKEN_ID,
+        new_dual_yield_nonce,
+        &rust_biguint!(dual_yield_amount),
+        None,
+    );
+}
+
+#[test]
+fn compound_into_existing_metastaking_position_test() {
+    DebugApi::dummy();
+    let mut ms_setup = MetastakingSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+        pair::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = ms_setup.af_setup.farm_setup.b_mock.clone();
+    let owner = ms_setup.af_setup.farm_setup.owner.clone();
+    let first_user = ms_setup.af_setup.farm_setup.first_user.clone();
+    let proxy_address = ms_setup.af_setup.proxy_address.clone();
+    let auto_farm_addr = ms_setup.af_setup.auto_farm_wrapper.address_ref().clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    // user enters metastaking and deposits the dual yield tokens
+    let dual_yield_amount = 100_000_000;
+    let dual_yield_nonce = ms_setup.enter_metastaking(&first_user, 1, dual_yield_amount);
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_nonce,
+            &rust_biguint!(dual_yield_amount),
+            |sc| {
+                sc.deposit_metastaking_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_lp_compound_max_slippage(500);
+        })
+        .assert_ok();
+
+    // advance to the next week, so the user has boosted rewards in the LP farm
+    b_mock.borrow_mut().set_block_nonce(10);
+    b_mock.borrow_mut().set_block_epoch(10);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 10, 1);
+
+    // the user has token A rewards, held by auto-farm
+    let compound_amount = 1_000_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        &auto_farm_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(compound_amount),
+    );
+
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                opt_locked_tokens: None,
+                other_tokens: UniquePayments::new_from_unique_payments(
+                    ManagedVec::from_single_item(EsdtTokenPayment::new(
+                        managed_token_id!(TOKEN_IDS[0]),
+                        0,
+                        managed_biguint!(compound_amount),
+                    )),
+                ),
+            });
+
+            sc.compound_user_rewards(
+                &managed_address!(&first_user),
+                user_id,
+                CompoundStrategy::CompoundIntoMetastaking,
+            );
+
+            // the LP farm tokens were merged into a new dual yield position
+            let positions = sc.user_metastaking_positions(user_id, 1).get();
+            assert_eq!(positions.len(), 1);
+            let new_position = positions.get(0);
+            assert_eq!(
+                new_position.token_identifier,
+                managed_token_id!(DUAL_YIELD_TOKEN_ID)
+            );
+            assert!(new_position.token_nonce > dual_yield_nonce);
+            assert!(new_position.amount > managed_biguint!(dual_yield_amount));
+
+            // the boosted rewards received while compounding were added to the user's rewards
+            let user_rewards = sc.user_rewards(user_id).get();
+            let locked_rewards = user_rewards.opt_locked_tokens.unwrap();
+            assert_eq!(
+                locked_rewards.token_identifier,
+                managed_token_id!(LOCKED_REWARD_TOKEN_ID)
+            );
+            assert!(locked_rewards.amount > 0);
+        })
+        .assert_ok();
+}
@@ -0,0 +1,264 @@
+#![allow(deprecated)]
+#![allow(dead_code)]
+
+use crate::auto_farm_setup::AutoFarmSetup;
+use crate::pair_setup::PairSetup;
+
+use auto_farm::whitelists::metastaking_whitelist::MetastakingWhitelistModule;
+use farm::exit_penalty::ExitPenaltyModule;
+use farm_staking_proxy::{
+    dual_yield_token::DualYieldTokenModule, proxy_actions::stake::ProxyStakeModule,
+    FarmStakingProxy,
+};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{Address, EsdtLocalRole, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint,
+    testing_framework::ContractObjWrapper, DebugApi,
+};
+use pair::safe_price::SafePriceModule;
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::{
+    farm_staking_setup::{setup_farm_staking, STAKING_FARM_TOKEN_ID},
+    farm_with_locked_rewards_setup::{FARMING_TOKEN_ID, FARM_TOKEN_ID},
+};
+
+pub static TOKEN_IDS: &[&[u8]] = &[b"FIRST-123456", b"SECOND-123456"];
+pub static DUAL_YIELD_TOKEN_ID: &[u8] = b"DUALYIELD-123456";
+
+pub const LP_FARM_INDEX: usize = 0;
+
+pub struct MetastakingSetup<
+    FarmBuilder,
+    EnergyFactoryBuilder,
+    AutoFarmBuilder,
+    PairBuilder,
+    FarmStakingBuilder,
+    MetastakingBuilder,
+> where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+    PairBuilder: 'static + Copy + Fn() -> pair::ContractObj<DebugApi>,
+    FarmStakingBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    MetastakingBuilder: 'static + Copy + Fn() -> farm_staking_proxy::ContractObj<DebugApi>,
+{
+    pub af_setup: AutoFarmSetup<FarmBuilder, EnergyFactoryBuilder, AutoFarmBuilder>,
+    pub pair_setup: PairSetup<PairBuilder>,
+    pub fs_wrapper: ContractObjWrapper<farm_staking::ContractObj<DebugApi>, FarmStakingBuilder>,
+    pub ms_wrapper:
+        ContractObjWrapper<farm_staking_proxy::ContractObj<DebugApi>, MetastakingBuilder>,
+}
+
+impl<
+        FarmBuilder,
+        EnergyFactoryBuilder,
+        AutoFarmBuilder,
+        PairBuilder,
+        FarmStakingBuilder,
+        MetastakingBuilder,
+    >
+    MetastakingSetup<
+        FarmBuilder,
+        EnergyFactoryBuilder,
+        AutoFarmBuilder,
+        PairBuilder,
+        FarmStakingBuilder,
+        MetastakingBuilder,
+    >
+where
+    FarmBuilder: 'static + Copy + Fn() -> farm_with_locked_rewards::ContractObj<DebugApi>,
+    EnergyFactoryBuilder: 'static + Copy + Fn() -> energy_factory::ContractObj<DebugApi>,
+    AutoFarmBuilder: 'static + Copy + Fn() -> auto_farm::ContractObj<DebugApi>,
+    PairBuilder: 'static + Copy + Fn() -> pair::ContractObj<DebugApi>,
+    FarmStakingBuilder: 'static + Copy + Fn() -> farm_staking::ContractObj<DebugApi>,
+    MetastakingBuilder: 'static + Copy + Fn() -> farm_staking_proxy::ContractObj<DebugApi>,
+{
+    // Pool (A, B) => (1B, 2B), its LP token is the farming token of the first farm.
+    // Farm staking stakes and rewards token A.
+    pub fn new(
+        farm_builder: FarmBuilder,
+        energy_factory_builder: EnergyFactoryBuilder,
+        auto_farm_builder: AutoFarmBuilder,
+        pair_builder: PairBuilder,
+        farm_staking_builder: FarmStakingBuilder,
+        metastaking_builder: MetastakingBuilder,
+    ) -> Self {
+        let rust_zero = rust_biguint!(0);
+        let mut af_setup =
+            AutoFarmSetup::new(farm_builder, energy_factory_builder, auto_farm_builder);
+        af_setup.add_farms();
+
+        let b_mock = af_setup.farm_setup.b_mock.clone();
+        let owner = af_setup.farm_setup.owner.clone();
+        let lp_farm_addr = af_setup.farm_setup.farm_wrappers[LP_FARM_INDEX]
+            .address_ref()
+            .clone();
+        let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+
+        let mut pair_setup = PairSetup::new(
+            b_mock.clone(),
+            pair_builder,
+            &owner,
+            Some(&lp_farm_addr),
+            TOKEN_IDS[0],
+            TOKEN_IDS[1],
+            FARMING_TOKEN_ID[LP_FARM_INDEX],
+        );
+
+        b_mock
+            .borrow_mut()
+            .set_esdt_balance(&owner, TOKEN_IDS[0], &rust_biguint!(1_000_000_000));
+        b_mock
+            .borrow_mut()
+            .set_esdt_balance(&owner, TOKEN_IDS[1], &rust_biguint!(2_000_000_000));
+
+        let mut block_round: u64 = 1;
+        b_mock.borrow_mut().set_block_round(block_round);
+        pair_setup.add_liquidity(&owner, 1_000_000_000, 2_000_000_000);
+
+        // setup price observations
+        for _i in 1usize..=20 {
+            block_round += 1;
+            b_mock.borrow_mut().set_block_round(block_round);
+
+            b_mock
+                .borrow_mut()
+                .execute_tx(&owner, &pair_setup.pair_wrapper, &rust_zero, |sc| {
+                    sc.update_safe_price(
+                        &managed_biguint!(1_000_000_000),
+                        &managed_biguint!(2_000_000_000),
+                        &managed_biguint!(1_000_000_000),
+                    );
+                })
+                .assert_ok();
+        }
+
+        let fs_wrapper = setup_farm_staking(
+            &mut b_mock.borrow_mut(),
+            farm_staking_builder,
+            TOKEN_IDS[0],
+            TOKEN_IDS[0],
+        );
+
+        let ms_wrapper = b_mock.borrow_mut().create_sc_account(
+            &rust_zero,
+            Some(&owner),
+            metastaking_builder,
+            "metastaking",
+        );
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &ms_wrapper, &rust_zero, |sc| {
+                sc.init(
+                    managed_address!(af_setup.farm_setup.energy_factory_wrapper.address_ref()),
+                    managed_address!(&lp_farm_addr),
+                    managed_address!(fs_wrapper.address_ref()),
+                    managed_address!(pair_setup.pair_wrapper.address_ref()),
+                    managed_token_id!(TOKEN_IDS[0]),
+                    managed_token_id!(FARM_TOKEN_ID[LP_FARM_INDEX]),
+                    managed_token_id!(STAKING_FARM_TOKEN_ID),
+                    managed_token_id!(FARMING_TOKEN_ID[LP_FARM_INDEX]),
+                );
+
+                sc.dual_yield_token()
+                    .set_token_id(managed_token_id!(DUAL_YIELD_TOKEN_ID));
+
+                sc.sc_whitelist_addresses()
+                    .add(&managed_address!(&auto_farm_addr));
+            })
+            .assert_ok();
+
+        let dual_yield_token_roles = [
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ];
+        b_mock.borrow_mut().set_esdt_local_roles(
+            ms_wrapper.address_ref(),
+            DUAL_YIELD_TOKEN_ID,
+            &dual_yield_token_roles[..],
+        );
+
+        // whitelist auto-farm and metastaking in farm staking
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &fs_wrapper, &rust_zero, |sc| {
+                sc.sc_whitelist_addresses()
+                    .add(&managed_address!(&auto_farm_addr));
+                sc.sc_whitelist_addresses()
+                    .add(&managed_address!(ms_wrapper.address_ref()));
+            })
+            .assert_ok();
+
+        // whitelist metastaking in the LP farm and set the pair address
+        b_mock
+            .borrow_mut()
+            .execute_tx(
+                &owner,
+                &af_setup.farm_setup.farm_wrappers[LP_FARM_INDEX],
+                &rust_zero,
+                |sc| {
+                    sc.sc_whitelist_addresses()
+                        .add(&managed_address!(ms_wrapper.address_ref()));
+                    sc.pair_contract_address()
+                        .set(managed_address!(pair_setup.pair_wrapper.address_ref()));
+                    sc.penalty_percent().set(0u64);
+                },
+            )
+            .assert_ok();
+
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                let mut args = MultiValueEncoded::new();
+                args.push(managed_address!(ms_wrapper.address_ref()));
+                sc.add_metastaking_scs(args);
+            })
+            .assert_ok();
+
+        MetastakingSetup {
+            af_setup,
+            pair_setup,
+            fs_wrapper,
+            ms_wrapper,
+        }
+    }
+
+    /// Enters the LP farm and stakes the new farm token in metastaking.
+    /// Returns the nonce of the dual yield token.
+    pub fn enter_metastaking(
+        &mut self,
+        user: &Address,
+        farm_token_nonce: u64,
+        farming_token_amount: u64,
+    ) -> u64 {
+        self.af_setup
+            .farm_setup
+            .enter_farm(LP_FARM_INDEX, user, farming_token_amount);
+
+        let mut dual_yield_token_nonce = 0;
+        self.af_setup
+            .farm_setup
+            .b_mock
+            .borrow_mut()
+            .execute_esdt_transfer(
+                user,
+                &self.ms_wrapper,
+                FARM_TOKEN_ID[LP_FARM_INDEX],
+                farm_token_nonce,
+                &rust_biguint!(farming_token_amount),
+                |sc| {
+                    let stake_result = sc.stake_farm_tokens(OptionalValue::None);
+                    assert_eq!(
+                        stake_result.dual_yield_tokens.token_identifier,
+                        managed_token_id!(DUAL_YIELD_TOKEN_ID)
+                    );
+                    dual_yield_token_nonce = stake_result.dual_yield_tokens.token_nonce;
+                },
+            )
+            .assert_ok();
+
+        dual_yield_token_nonce
+    }
+}
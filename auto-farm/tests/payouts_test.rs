@@ -0,0 +1,232 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::MergedRewardsWrapper;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::user_payouts::UserPayoutsModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+
+static FIRST_TOKEN_ID: &[u8] = b"FIRST-123456";
+static SECOND_TOKEN_ID: &[u8] = b"SECOND-123456";
+
+#[test]
+fn payout_frequency_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let rewards_amount = 1_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        af_setup.auto_farm_wrapper.address_ref(),
+        FIRST_TOKEN_ID,
+        &rust_biguint!(rewards_amount),
+    );
+
+    b_mock.borrow_mut().set_block_epoch(10);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+            sc.set_payout_frequency(5);
+
+            let user_id = 1;
+            sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                opt_locked_tokens: None,
+                other_tokens: UniquePayments::new_from_unique_payments(
+                    ManagedVec::from_single_item(EsdtTokenPayment::new(
+                        managed_token_id!(FIRST_TOKEN_ID),
+                        0,
+                        managed_biguint!(rewards_amount),
+                    )),
+                ),
+            });
+
+            assert_eq!(
+                sc.get_user_payout_frequency_view(managed_address!(&first_user)),
+                5
+            );
+            assert_eq!(
+                sc.get_user_last_payout_epoch_view(managed_address!(&first_user)),
+                10
+            );
+        })
+        .assert_ok();
+
+    // the payout period has not passed yet
+    b_mock.borrow_mut().set_block_epoch(14);
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &af_setup.proxy_address,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.process_auto_payouts(&managed_address!(&first_user), 1);
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow()
+        .check_esdt_balance(&first_user, FIRST_TOKEN_ID, &rust_zero);
+
+    b_mock.borrow_mut().set_block_epoch(15);
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &af_setup.proxy_address,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.process_auto_payouts(&managed_address!(&first_user), 1);
+
+                assert_eq!(
+                    sc.get_user_last_payout_epoch_view(managed_address!(&first_user)),
+                    15
+                );
+                assert!(sc.user_rewards(1).is_empty());
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow()
+        .check_esdt_balance(&first_user, FIRST_TOKEN_ID, &rust_biguint!(rewards_amount));
+
+    // 0 disables the periodic payouts
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_payout_frequency(0);
+
+            assert_eq!(
+                sc.get_user_payout_frequency_view(managed_address!(&first_user)),
+                0
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn payout_thresholds_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let first_token_amount = 600;
+    let second_token_amount = 400;
+    let threshold = 500;
+    b_mock.borrow_mut().set_esdt_balance(
+        af_setup.auto_farm_wrapper.address_ref(),
+        FIRST_TOKEN_ID,
+        &rust_biguint!(first_token_amount),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        af_setup.auto_farm_wrapper.address_ref(),
+        SECOND_TOKEN_ID,
+        &rust_biguint!(second_token_amount),
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+
+            let mut thresholds = MultiValueEncoded::new();
+            thresholds.push(
+                (
+                    managed_token_id!(FIRST_TOKEN_ID),
+                    managed_biguint!(threshold),
+                )
+                    .into(),
+            );
+            thresholds.push(
+                (
+                    managed_token_id!(SECOND_TOKEN_ID),
+                    managed_biguint!(threshold),
+                )
+                    .into(),
+            );
+            sc.set_payout_thresholds(thresholds);
+
+            // a min amount of 0 removes the threshold
+            let mut thresholds = MultiValueEncoded::new();
+            thresholds.push((managed_token_id!(SECOND_TOKEN_ID), managed_biguint!(0)).into());
+            sc.set_payout_thresholds(thresholds);
+            assert_eq!(
+                sc.get_user_payout_thresholds_view(managed_address!(&first_user))
+                    .len(),
+                1
+            );
+
+            let mut rewards = ManagedVec::new();
+            rewards.push(EsdtTokenPayment::new(
+                managed_token_id!(FIRST_TOKEN_ID),
+                0,
+                managed_biguint!(first_token_amount),
+            ));
+            rewards.push(EsdtTokenPayment::new(
+                managed_token_id!(SECOND_TOKEN_ID),
+                0,
+                managed_biguint!(second_token_amount),
+            ));
+
+            let user_id = 1;
+            sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                opt_locked_tokens: None,
+                other_tokens: UniquePayments::new_from_unique_payments(rewards),
+            });
+        })
+        .assert_ok();
+
+    // only the tokens over their threshold are sent
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &af_setup.proxy_address,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.process_auto_payouts(&managed_address!(&first_user), 1);
+
+                let expected_remaining = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                    managed_token_id!(SECOND_TOKEN_ID),
+                    0,
+                    managed_biguint!(second_token_amount),
+                ));
+                assert_eq!(
+                    sc.get_user_rewards_view(managed_address!(&first_user))
+                        .other_tokens
+                        .into_payments(),
+                    expected_remaining
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &first_user,
+        FIRST_TOKEN_ID,
+        &rust_biguint!(first_token_amount),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&first_user, SECOND_TOKEN_ID, &rust_zero);
+}
@@ -0,0 +1,203 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod metastaking_setup;
+pub mod pair_setup;
+
+use auto_farm::portfolio::PortfolioModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule;
+use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
+use auto_farm_setup::AutoFarmSetup;
+use energy_query::EnergyQueryModule;
+use farm_staking::stake_farm::StakeFarmModule;
+use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
+use metastaking_setup::{MetastakingSetup, DUAL_YIELD_TOKEN_ID};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::contract_base::ContractBase;
+use multiversx_sc::types::{MultiValueEncoded, TokenIdentifier};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use tests_common::farm_staking_setup::{setup_farm_staking, STAKING_FARM_TOKEN_ID};
+
+static STAKING_TOKEN_ID: &[u8] = b"STAKE-123456";
+
+#[test]
+fn staking_farm_position_portfolio_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+
+    let fs_wrapper = setup_farm_staking(
+        &mut b_mock.borrow_mut(),
+        farm_staking::contract_obj,
+        STAKING_TOKEN_ID,
+        STAKING_TOKEN_ID,
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fs_wrapper, &rust_zero, |sc| {
+            sc.energy_factory_address()
+                .set(managed_address!(&energy_factory_addr));
+        })
+        .assert_ok();
+
+    let fs_addr = fs_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut farms = MultiValueEncoded::new();
+            farms.push(managed_address!(&fs_addr));
+            sc.add_farms(farms);
+        })
+        .assert_ok();
+
+    // user stakes and deposits the staking farm tokens
+    let stake_amount = 1_000_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user,
+        STAKING_TOKEN_ID,
+        &rust_biguint!(stake_amount),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &fs_wrapper,
+            STAKING_TOKEN_ID,
+            0,
+            &rust_biguint!(stake_amount),
+            |sc| {
+                let _ = sc.stake_farm_endpoint(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            STAKING_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(stake_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&af_setup.auto_farm_wrapper, |sc| {
+            let portfolio = sc.get_user_portfolio(managed_address!(&first_user));
+            assert_eq!(portfolio.farm_positions.len(), 1);
+            assert!(portfolio.metastaking_positions.is_empty());
+
+            let position_info = portfolio.farm_positions.get(0);
+            assert_eq!(
+                position_info.position.token_identifier,
+                managed_token_id!(STAKING_FARM_TOKEN_ID)
+            );
+            assert_eq!(position_info.sc_address, managed_address!(&fs_addr));
+
+            // the staked tokens, without a pair
+            assert_eq!(
+                position_info.first_tokens.token_identifier,
+                managed_token_id!(STAKING_TOKEN_ID)
+            );
+            assert_eq!(
+                position_info.first_tokens.amount,
+                managed_biguint!(stake_amount)
+            );
+            assert_eq!(
+                position_info.lp_tokens.token_identifier,
+                TokenIdentifier::from(&b""[..])
+            );
+            assert_eq!(position_info.lp_tokens.amount, managed_biguint!(0));
+            assert_eq!(position_info.second_tokens.amount, managed_biguint!(0));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn partial_metastaking_position_portfolio_test() {
+    DebugApi::dummy();
+    let mut ms_setup = MetastakingSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+        pair::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let b_mock = ms_setup.af_setup.farm_setup.b_mock.clone();
+    let first_user = ms_setup.af_setup.farm_setup.first_user.clone();
+    let ms_addr = ms_setup.ms_wrapper.address_ref().clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    ms_setup
+        .af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    // user deposits only half of the dual yield tokens
+    let dual_yield_amount = 100_000_000;
+    let deposit_amount = dual_yield_amount / 2;
+    let dual_yield_nonce = ms_setup.enter_metastaking(&first_user, 1, dual_yield_amount);
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            dual_yield_nonce,
+            &rust_biguint!(deposit_amount),
+            |sc| {
+                sc.deposit_metastaking_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    // the amounts of the dual yield token nonce are scaled down to the deposited share
+    b_mock
+        .borrow_mut()
+        .execute_query(auto_farm_wrapper, |sc| {
+            let portfolio = sc.get_user_portfolio(managed_address!(&first_user));
+            assert!(portfolio.farm_positions.is_empty());
+            assert_eq!(portfolio.metastaking_positions.len(), 1);
+
+            let position_info = portfolio.metastaking_positions.get(0);
+            assert_eq!(position_info.sc_address, managed_address!(&ms_addr));
+            assert_eq!(
+                position_info.position.amount,
+                managed_biguint!(deposit_amount)
+            );
+
+            let attributes: DualYieldTokenAttributes<DebugApi> = sc
+                .blockchain()
+                .get_token_attributes(&managed_token_id!(DUAL_YIELD_TOKEN_ID), dual_yield_nonce);
+            let expected_lp_amount = &attributes.lp_farm_token_amount
+                * &managed_biguint!(deposit_amount)
+                / &attributes.virtual_pos_token_amount;
+            assert_eq!(position_info.lp_tokens.amount, expected_lp_amount);
+            assert!(position_info.lp_tokens.amount < attributes.lp_farm_token_amount);
+        })
+        .assert_ok();
+}
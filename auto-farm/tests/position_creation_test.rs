@@ -0,0 +1,132 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod metastaking_setup;
+pub mod pair_setup;
+pub mod router_setup;
+
+use auto_farm::external_sc_interactions::position_creation::PositionCreationModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use metastaking_setup::{MetastakingSetup, LP_FARM_INDEX, TOKEN_IDS};
+use multiversx_sc::types::MultiValueEncoded;
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use router_setup::RouterSetup;
+use tests_common::farm_with_locked_rewards_setup::FARM_TOKEN_ID;
+
+#[test]
+fn make_me_a_position_swap_slippage_test() {
+    DebugApi::dummy();
+    let ms_setup = MetastakingSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+        pair::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+    );
+
+    let rust_zero = rust_biguint!(0);
+    let b_mock = ms_setup.af_setup.farm_setup.b_mock.clone();
+    let owner = ms_setup.af_setup.farm_setup.owner.clone();
+    let first_user = ms_setup.af_setup.farm_setup.first_user.clone();
+    let pair_addr = ms_setup.pair_setup.pair_wrapper.address_ref().clone();
+    let lp_farm_addr = ms_setup.af_setup.farm_setup.farm_wrappers[LP_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    let mut router_setup =
+        RouterSetup::new(b_mock.clone(), router::contract_obj, &owner, &pair_addr);
+    router_setup.whitelist_pair(&owner, TOKEN_IDS[0], TOKEN_IDS[1], &pair_addr);
+
+    // EGLD payments are not used here, so the router also stands in for the EGLD wrapper
+    let router_addr = router_setup.router_wrapper.address_ref().clone();
+    let auto_farm_wrapper = &ms_setup.af_setup.auto_farm_wrapper;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_position_creation_addresses(
+                managed_address!(&router_addr),
+                managed_address!(&router_addr),
+            );
+        })
+        .assert_ok();
+
+    // half of the input is swapped at a 1:2 price, without fees
+    let input_amount = 100_000;
+    let expected_swap_output = 99_995;
+    b_mock
+        .borrow_mut()
+        .set_esdt_balance(&first_user, TOKEN_IDS[0], &rust_biguint!(input_amount));
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(input_amount),
+            |sc| {
+                let _ = sc.make_me_a_position(
+                    managed_address!(&lp_farm_addr),
+                    managed_biguint!(expected_swap_output + 1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("Swap output too low");
+
+    // a swap without a slippage limit is rejected
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(input_amount),
+            |sc| {
+                let _ = sc.make_me_a_position(
+                    managed_address!(&lp_farm_addr),
+                    managed_biguint!(0),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("Invalid slippage limits");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            auto_farm_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(input_amount),
+            |sc| {
+                let _ = sc.make_me_a_position(
+                    managed_address!(&lp_farm_addr),
+                    managed_biguint!(expected_swap_output),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    MultiValueEncoded::new(),
+                );
+
+                let user_id = 1;
+                let farm_id = 1;
+                let positions = sc.user_farm_positions(user_id, farm_id).get();
+                assert_eq!(positions.len(), 1);
+                assert_eq!(
+                    positions.get(0).token_identifier,
+                    managed_token_id!(FARM_TOKEN_ID[LP_FARM_INDEX])
+                );
+            },
+        )
+        .assert_ok();
+}
@@ -0,0 +1,209 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::MergedRewardsWrapper;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::deposit_caps::DepositCapsModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::position_transfer::PositionTransferModule;
+use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX};
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use tests_common::farm_with_locked_rewards_setup::FARM_TOKEN_ID;
+
+static REWARD_TOKEN_ID: &[u8] = b"REW-123456";
+
+#[test]
+fn transfer_positions_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let second_user = af_setup.farm_setup.second_user.clone();
+    let first_farm = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+    let farm_token_amount = 100_000_000;
+    let rewards_amount = 1_000;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, farm_token_amount);
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_farm_tokens(OptionalValue::None);
+
+                let user_id = 1;
+                sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                    opt_locked_tokens: None,
+                    other_tokens: UniquePayments::new_from_unique_payments(
+                        ManagedVec::from_single_item(EsdtTokenPayment::new(
+                            managed_token_id!(REWARD_TOKEN_ID),
+                            0,
+                            managed_biguint!(rewards_amount),
+                        )),
+                    ),
+                });
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &second_user,
+            &af_setup.auto_farm_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.register();
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(farm_token_amount),
+            ));
+            sc.transfer_positions(managed_address!(&first_user), positions, false);
+        })
+        .assert_user_error("Cannot transfer to yourself");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(farm_token_amount + 1),
+            ));
+            sc.transfer_positions(managed_address!(&second_user), positions, false);
+        })
+        .assert_user_error("Not enough balance to withdraw");
+
+    // the receiver's deposit cap is checked
+    let user_cap = 50_000_000;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_deposit_caps(
+                managed_address!(&first_farm),
+                managed_biguint!(0),
+                managed_biguint!(user_cap),
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(user_cap + 1),
+            ));
+            sc.transfer_positions(managed_address!(&second_user), positions, false);
+        })
+        .assert_user_error("User deposit cap exceeded");
+
+    // partial transfer, together with the rewards
+    let transfer_amount = 40_000_000;
+    let tx_result = b_mock.borrow_mut().execute_tx(
+        &first_user,
+        &af_setup.auto_farm_wrapper,
+        &rust_zero,
+        |sc| {
+            let positions = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(transfer_amount),
+            ));
+            sc.transfer_positions(managed_address!(&second_user), positions, true);
+
+            let (first_user_id, second_user_id, farm_id) = (1, 2, 1);
+            assert_eq!(
+                sc.user_farm_positions(first_user_id, farm_id)
+                    .get()
+                    .get(0)
+                    .amount,
+                managed_biguint!(farm_token_amount - transfer_amount)
+            );
+            assert_eq!(
+                sc.user_farm_positions(second_user_id, farm_id)
+                    .get()
+                    .get(0)
+                    .amount,
+                managed_biguint!(transfer_amount)
+            );
+
+            let (first_user_usage, _) = sc
+                .get_user_deposit_usage(
+                    managed_address!(&first_user),
+                    managed_address!(&first_farm),
+                )
+                .into_tuple();
+            assert_eq!(
+                first_user_usage,
+                managed_biguint!(farm_token_amount - transfer_amount)
+            );
+            let (second_user_usage, _) = sc
+                .get_user_deposit_usage(
+                    managed_address!(&second_user),
+                    managed_address!(&first_farm),
+                )
+                .into_tuple();
+            assert_eq!(second_user_usage, managed_biguint!(transfer_amount));
+            let (total_usage, _) = sc
+                .get_total_deposit_usage(managed_address!(&first_farm))
+                .into_tuple();
+            assert_eq!(total_usage, managed_biguint!(farm_token_amount));
+
+            assert!(sc.user_rewards(first_user_id).is_empty());
+            let expected_rewards = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(REWARD_TOKEN_ID),
+                0,
+                managed_biguint!(rewards_amount),
+            ));
+            assert_eq!(
+                sc.get_user_rewards_view(managed_address!(&second_user))
+                    .other_tokens
+                    .into_payments(),
+                expected_rewards
+            );
+        },
+    );
+    tx_result.assert_ok();
+
+    let transfer_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"positionsTransfer".to_vec())
+        .count();
+    assert_eq!(transfer_event_logs, 1);
+}
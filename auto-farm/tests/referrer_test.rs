@@ -0,0 +1,105 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::fees::FeesModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::ManagedAddress;
+use multiversx_sc_scenario::{managed_address, rust_biguint, DebugApi};
+
+#[test]
+fn set_referrer_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user;
+    let second_user = farm_setup.second_user;
+    let third_user = farm_setup.third_user;
+
+    // try set referrer before register
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_referrer(managed_address!(&second_user));
+        })
+        .assert_user_error("Unknown address");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_referrer(managed_address!(&first_user));
+        })
+        .assert_user_error("Cannot refer yourself");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_referrer(ManagedAddress::zero());
+        })
+        .assert_user_error("Invalid referrer");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_referrer(managed_address!(&second_user));
+
+            assert_eq!(
+                sc.get_user_referrer_view(managed_address!(&first_user)),
+                OptionalValue::Some(managed_address!(&second_user))
+            );
+        })
+        .assert_ok();
+
+    // the referrer cannot be changed afterwards
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_referrer(managed_address!(&third_user));
+        })
+        .assert_user_error("Referrer already set");
+
+    // unregister and register again - referrer is reset
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.withdraw_all_and_unregister();
+            sc.register();
+
+            assert_eq!(
+                sc.get_user_referrer_view(managed_address!(&first_user)),
+                OptionalValue::None
+            );
+
+            sc.set_referrer(managed_address!(&third_user));
+            assert_eq!(
+                sc.get_user_referrer_view(managed_address!(&first_user)),
+                OptionalValue::Some(managed_address!(&third_user))
+            );
+        })
+        .assert_ok();
+}
@@ -0,0 +1,124 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::user_delegates::{
+    UserDelegatesModule, CHANGE_STRATEGY_RIGHT, CLAIM_REWARDS_RIGHT,
+};
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::user_tokens::user_strategy::{CompoundStrategy, UserStrategyModule};
+use auto_farm_setup::AutoFarmSetup;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc_scenario::{managed_address, rust_biguint};
+
+#[test]
+fn user_delegates_test() {
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user.clone();
+    let delegate = farm_setup.second_user.clone();
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(5);
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+            sc.add_delegate(managed_address!(&delegate), CHANGE_STRATEGY_RIGHT, 10);
+        })
+        .assert_ok();
+
+    // delegate changes the strategy
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&delegate, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(
+                CompoundStrategy::ClaimOnly,
+                OptionalValue::Some(managed_address!(&first_user)),
+            );
+
+            assert_eq!(
+                sc.get_user_compound_strategy_view(managed_address!(&first_user)),
+                CompoundStrategy::ClaimOnly
+            );
+        })
+        .assert_ok();
+
+    // delegate try claim rewards without the right
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&delegate, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ =
+                sc.user_claim_rewards_endpoint(OptionalValue::Some(managed_address!(&first_user)));
+        })
+        .assert_user_error("Delegate does not have the required rights");
+
+    // update rights
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.add_delegate(
+                managed_address!(&delegate),
+                CHANGE_STRATEGY_RIGHT | CLAIM_REWARDS_RIGHT,
+                10,
+            );
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&delegate, &auto_farm_wrapper, &rust_zero, |sc| {
+            let _ =
+                sc.user_claim_rewards_endpoint(OptionalValue::Some(managed_address!(&first_user)));
+        })
+        .assert_ok();
+
+    // delegation expired
+    farm_setup.b_mock.borrow_mut().set_block_epoch(10);
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&delegate, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(
+                CompoundStrategy::SendToWallet,
+                OptionalValue::Some(managed_address!(&first_user)),
+            );
+        })
+        .assert_user_error("Delegation expired");
+
+    // revoke delegate
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.remove_delegate(managed_address!(&delegate));
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&delegate, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(
+                CompoundStrategy::SendToWallet,
+                OptionalValue::Some(managed_address!(&first_user)),
+            );
+        })
+        .assert_user_error("Not a delegate of the user");
+}
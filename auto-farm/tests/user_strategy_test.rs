@@ -0,0 +1,241 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod fees_collector_setup;
+
+use auto_farm::common::common_storage::CommonStorageModule;
+use auto_farm::common::rewards_wrapper::MergedRewardsWrapper;
+use auto_farm::common::unique_payments::UniquePayments;
+use auto_farm::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule;
+use auto_farm::external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm::user_tokens::user_rewards::UserRewardsModule;
+use auto_farm::user_tokens::user_strategy::{CompoundStrategy, UserStrategyModule};
+use auto_farm_setup::AutoFarmSetup;
+use fees_collector_setup::setup_fees_collector;
+use multiversx_sc::codec::multi_types::OptionalValue;
+use multiversx_sc::types::{EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use sc_whitelist_module::SCWhitelistModule;
+
+static REWARD_TOKEN_ID: &[u8] = b"REWARD-123456";
+
+#[test]
+fn set_compound_strategy_test() {
+    let rust_zero = rust_biguint!(0);
+    let AutoFarmSetup {
+        farm_setup,
+        proxy_address: _,
+        auto_farm_wrapper,
+    } = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let first_user = farm_setup.first_user;
+
+    // try set strategy before register
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(CompoundStrategy::ClaimOnly, OptionalValue::None);
+        })
+        .assert_user_error("Unknown address");
+
+    // register - default strategy
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+
+            assert_eq!(
+                sc.get_user_compound_strategy_view(managed_address!(&first_user)),
+                CompoundStrategy::CompoundIntoStaking
+            );
+        })
+        .assert_ok();
+
+    // set strategy
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_compound_strategy(CompoundStrategy::SendToWallet, OptionalValue::None);
+
+            assert_eq!(
+                sc.get_user_compound_strategy_view(managed_address!(&first_user)),
+                CompoundStrategy::SendToWallet
+            );
+        })
+        .assert_ok();
+
+    // unregister - the stored strategy is cleared
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = sc.user_ids().get_id(&managed_address!(&first_user));
+            assert_eq!(user_id, 1);
+            assert_eq!(
+                sc.user_strategy(user_id).get(),
+                CompoundStrategy::SendToWallet
+            );
+
+            let _ = sc.withdraw_all_and_unregister();
+
+            assert!(sc.user_strategy(user_id).is_empty());
+        })
+        .assert_ok();
+
+    // register again - the user gets a new id, with the default strategy
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+
+            assert_eq!(sc.user_ids().get_id(&managed_address!(&first_user)), 2);
+            assert_eq!(
+                sc.get_user_compound_strategy_view(managed_address!(&first_user)),
+                CompoundStrategy::CompoundIntoStaking
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn claim_with_each_strategy_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let keeper = af_setup.proxy_address.clone();
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+
+    let fc_wrapper = setup_fees_collector(
+        &mut b_mock.borrow_mut(),
+        fees_collector::contract_obj,
+        &energy_factory_addr,
+        &af_setup.farm_setup.first_user,
+        &af_setup.farm_setup.second_user,
+        &af_setup.farm_setup.third_user,
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fc_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.fees_collector_sc_address()
+                .set(managed_address!(fc_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    // one user per strategy, each with the same rewards held by auto-farm
+    let strategies = [
+        CompoundStrategy::ClaimOnly,
+        CompoundStrategy::SendToWallet,
+        CompoundStrategy::CompoundIntoStaking,
+    ];
+    let rewards_amount = 1_000;
+    b_mock.borrow_mut().set_esdt_balance(
+        &auto_farm_addr,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(rewards_amount * strategies.len() as u64),
+    );
+
+    let mut users = Vec::new();
+    for (i, strategy) in strategies.iter().enumerate() {
+        let user = b_mock.borrow_mut().create_user_account(&rust_zero);
+        b_mock
+            .borrow_mut()
+            .execute_tx(&user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                sc.register();
+                sc.set_compound_strategy(*strategy, OptionalValue::None);
+
+                let user_id = i as u64 + 1;
+                sc.user_rewards(user_id).set(MergedRewardsWrapper {
+                    opt_locked_tokens: None,
+                    other_tokens: UniquePayments::new_from_unique_payments(
+                        ManagedVec::from_single_item(EsdtTokenPayment::new(
+                            managed_token_id!(REWARD_TOKEN_ID),
+                            0,
+                            managed_biguint!(rewards_amount),
+                        )),
+                    ),
+                });
+            })
+            .assert_ok();
+        users.push(user);
+    }
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut claim_args = MultiValueEncoded::new();
+            for user in &users {
+                claim_args.push(managed_address!(user));
+            }
+            sc.claim_all_rewards_and_compound(claim_args);
+        })
+        .assert_ok();
+
+    // claim only - the rewards are kept by auto-farm
+    // send to wallet - the rewards are sent to the user
+    // compound into staking - with no staking position to compound into, the rewards are kept
+    b_mock
+        .borrow_mut()
+        .execute_query(&af_setup.auto_farm_wrapper, |sc| {
+            let kept_rewards = ManagedVec::from_single_item(EsdtTokenPayment::new(
+                managed_token_id!(REWARD_TOKEN_ID),
+                0,
+                managed_biguint!(rewards_amount),
+            ));
+            assert_eq!(
+                sc.user_rewards(1).get().other_tokens.into_payments(),
+                kept_rewards
+            );
+            assert!(sc.user_rewards(2).is_empty());
+            assert_eq!(
+                sc.user_rewards(3).get().other_tokens.into_payments(),
+                kept_rewards
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow()
+        .check_esdt_balance(&users[0], REWARD_TOKEN_ID, &rust_zero);
+    b_mock
+        .borrow()
+        .check_esdt_balance(&users[1], REWARD_TOKEN_ID, &rust_biguint!(rewards_amount));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&users[2], REWARD_TOKEN_ID, &rust_zero);
+    b_mock.borrow().check_esdt_balance(
+        &auto_farm_addr,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(rewards_amount * 2),
+    );
+}
@@ -0,0 +1,161 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+pub mod fees_collector_setup;
+
+use auto_farm::external_sc_interactions::fees_collector_actions::FeesCollectorActionsModule;
+use auto_farm::external_sc_interactions::users_processing::UsersProcessingModule;
+use auto_farm::registration::RegistrationModule;
+use auto_farm_setup::AutoFarmSetup;
+use fees_collector_setup::setup_fees_collector;
+use multiversx_sc::contract_base::ContractBase;
+use multiversx_sc_scenario::{managed_address, rust_biguint, DebugApi};
+use sc_whitelist_module::SCWhitelistModule;
+
+#[test]
+fn process_next_users_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let keeper = af_setup.proxy_address.clone();
+    let users = [
+        af_setup.farm_setup.first_user.clone(),
+        af_setup.farm_setup.second_user.clone(),
+        af_setup.farm_setup.third_user.clone(),
+    ];
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+
+    // processed users are claimed from fees collector
+    let fc_wrapper = setup_fees_collector(
+        &mut b_mock.borrow_mut(),
+        fees_collector::contract_obj,
+        &energy_factory_addr,
+        &users[0],
+        &users[1],
+        &users[2],
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &fc_wrapper, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.fees_collector_sc_address()
+                .set(managed_address!(fc_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    // without registered users, no full cycle is recorded
+    b_mock.borrow_mut().set_block_epoch(3);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            assert_eq!(sc.process_next_users(2), 0);
+
+            assert_eq!(sc.processing_cursor().get(), 0);
+            assert_eq!(sc.last_full_cycle_epoch().get(), 0);
+        })
+        .assert_ok();
+
+    for user in &users {
+        b_mock
+            .borrow_mut()
+            .execute_tx(user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                sc.register();
+            })
+            .assert_ok();
+    }
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&users[0], &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let _ = sc.process_next_users(1);
+        })
+        .assert_user_error("Only keepers can claim in user's place");
+
+    // the first batch stops at max_users
+    b_mock.borrow_mut().set_block_epoch(5);
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            assert_eq!(sc.process_next_users(2), 2);
+
+            let (cursor, last_user_id) = sc.get_processing_progress().into_tuple();
+            assert_eq!(cursor, 2);
+            assert_eq!(last_user_id, 3);
+            assert_eq!(sc.last_full_cycle_epoch().get(), 0);
+        })
+        .assert_ok();
+
+    // the second batch reaches the last user, and the cursor wraps around
+    let tx_result =
+        b_mock
+            .borrow_mut()
+            .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+                assert_eq!(sc.process_next_users(2), 1);
+
+                assert_eq!(sc.processing_cursor().get(), 0);
+                assert_eq!(sc.last_full_cycle_epoch().get(), 5);
+            });
+    tx_result.assert_ok();
+
+    let batch_event_logs = tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.topics[0] == b"batchProcessed".to_vec())
+        .count();
+    assert_eq!(batch_event_logs, 1);
+
+    // processing stops when the gas left is below the threshold
+    let mut gas_left = 0;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            gas_left = sc.blockchain().get_gas_left();
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_gas_for_processing(gas_left.saturating_add(1));
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            assert_eq!(sc.process_next_users(2), 0);
+            assert_eq!(sc.processing_cursor().get(), 0);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_gas_for_processing(0);
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            assert_eq!(sc.process_next_users(10), 3);
+            assert_eq!(sc.processing_cursor().get(), 0);
+        })
+        .assert_ok();
+}
@@ -0,0 +1,278 @@
+#![allow(deprecated)]
+
+pub mod auto_farm_setup;
+
+use auto_farm::common::rewards_wrapper::RewardsWrapper;
+use auto_farm::external_sc_interactions::proxy_dex_actions::ProxyDexActionsModule;
+use auto_farm::user_tokens::user_wrapped_farm_tokens::UserWrappedFarmTokensModule;
+use auto_farm::whitelists::proxy_dex_whitelist::ProxyDexWhitelistModule;
+use auto_farm_setup::{AutoFarmSetup, FIRST_FARM_INDEX};
+use energy_factory::locked_token_transfer::LockedTokenTransferModule;
+use energy_query::EnergyQueryModule;
+use multiversx_sc::types::{EsdtLocalRole, EsdtTokenPayment, ManagedVec, MultiValueEncoded};
+use multiversx_sc_scenario::{
+    managed_address, managed_biguint, managed_token_id, rust_biguint, DebugApi,
+};
+use pausable::{PausableModule, State};
+use proxy_dex::{
+    other_sc_whitelist::OtherScWhitelistModule, proxy_common::ProxyCommonModule,
+    wrapped_farm_attributes::WrappedFarmTokenAttributes, ProxyDexImpl,
+};
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::{FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID};
+
+static LEGACY_LOCKED_TOKEN_ID: &[u8] = b"LEGACY-123456";
+static WRAPPED_LP_TOKEN_ID: &[u8] = b"WPLP-123456";
+static WRAPPED_FARM_TOKEN_ID: &[u8] = b"WPFARM-123456";
+
+#[test]
+fn claim_wrapped_farm_rewards_test() {
+    DebugApi::dummy();
+    let rust_zero = rust_biguint!(0);
+    let mut af_setup = AutoFarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        auto_farm::contract_obj,
+    );
+    af_setup.add_farms();
+
+    let b_mock = af_setup.farm_setup.b_mock.clone();
+    let owner = af_setup.farm_setup.owner.clone();
+    let first_user = af_setup.farm_setup.first_user.clone();
+    let keeper = af_setup.proxy_address.clone();
+    let energy_factory_addr = af_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+    let auto_farm_addr = af_setup.auto_farm_wrapper.address_ref().clone();
+    let farm_addr = af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    // proxy-dex intermediates the first farm, and lets auto-farm claim in the user's place
+    let proxy_dex_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        proxy_dex::contract_obj,
+        "proxy dex",
+    );
+    let proxy_dex_addr = proxy_dex_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &proxy_dex_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id!(LEGACY_LOCKED_TOKEN_ID),
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr),
+            );
+
+            sc.wrapped_lp_token()
+                .set_token_id(managed_token_id!(WRAPPED_LP_TOKEN_ID));
+            sc.wrapped_farm_token()
+                .set_token_id(managed_token_id!(WRAPPED_FARM_TOKEN_ID));
+            sc.intermediated_farms()
+                .insert(managed_address!(&farm_addr));
+            sc.set_energy_factory_address(managed_address!(&energy_factory_addr));
+            sc.add_sc_address_to_whitelist(managed_address!(&auto_farm_addr));
+        })
+        .assert_ok();
+    b_mock.borrow_mut().set_esdt_local_roles(
+        &proxy_dex_addr,
+        WRAPPED_FARM_TOKEN_ID,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ],
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.add_sc_address_to_whitelist(managed_address!(&proxy_dex_addr));
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.energy_factory_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.add_sc_address_to_whitelist(managed_address!(&proxy_dex_addr));
+                sc.add_to_token_transfer_whitelist(
+                    ManagedVec::from_single_item(managed_address!(&proxy_dex_addr)).into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    // the proxy holds the farm tokens, and the user the wrapped farm tokens
+    b_mock.borrow_mut().set_block_epoch(2);
+    af_setup
+        .farm_setup
+        .set_user_energy(&first_user, 1_000, 2, 1);
+
+    let farm_token_amount = 100_000_000;
+    let wrapped_farm_token_nonce = 10;
+    af_setup
+        .farm_setup
+        .enter_farm(FIRST_FARM_INDEX, &first_user, farm_token_amount);
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &proxy_dex_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |_sc| {},
+        )
+        .assert_ok();
+    b_mock.borrow_mut().set_nft_balance(
+        &first_user,
+        WRAPPED_FARM_TOKEN_ID,
+        wrapped_farm_token_nonce,
+        &rust_biguint!(farm_token_amount),
+        &WrappedFarmTokenAttributes::<DebugApi> {
+            farm_token: EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(farm_token_amount),
+            ),
+            proxy_farming_token: EsdtTokenPayment::new(
+                managed_token_id!(LOCKED_REWARD_TOKEN_ID),
+                1,
+                managed_biguint!(farm_token_amount),
+            ),
+        },
+    );
+
+    // only the wrapped farm tokens of whitelisted proxy-dex contracts are accepted
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            WRAPPED_FARM_TOKEN_ID,
+            wrapped_farm_token_nonce,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_wrapped_farm_tokens();
+            },
+        )
+        .assert_user_error("Invalid token");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let mut scs = MultiValueEncoded::new();
+            scs.push(managed_address!(&proxy_dex_addr));
+            sc.add_proxy_dex_scs(scs);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &af_setup.auto_farm_wrapper,
+            WRAPPED_FARM_TOKEN_ID,
+            wrapped_farm_token_nonce,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_wrapped_farm_tokens();
+
+                let user_tokens =
+                    sc.get_user_wrapped_farm_tokens_view(managed_address!(&first_user));
+                assert_eq!(user_tokens.len(), 1);
+                assert_eq!(user_tokens.get(0).token_nonce, wrapped_farm_token_nonce);
+            },
+        )
+        .assert_ok();
+
+    // advance blocks, so the farm position has rewards
+    b_mock.borrow_mut().set_block_nonce(10);
+
+    // the rewards are claimed through proxy-dex, and the wrapped farm token is replaced
+    let mut new_wrapped_farm_token_nonce = 0;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.claim_all_wrapped_farm_rewards(
+                &managed_address!(&first_user),
+                user_id,
+                &mut rew_wrapper,
+            );
+
+            let locked_rewards = rew_wrapper.locked_tokens.into_payments();
+            assert_eq!(locked_rewards.len(), 1);
+            assert!(locked_rewards.get(0).amount > 0);
+
+            let user_tokens = sc.get_user_wrapped_farm_tokens_view(managed_address!(&first_user));
+            assert_eq!(user_tokens.len(), 1);
+            let new_wrapped_farm_token = user_tokens.get(0);
+            assert_eq!(
+                new_wrapped_farm_token.token_identifier,
+                managed_token_id!(WRAPPED_FARM_TOKEN_ID)
+            );
+            assert_ne!(new_wrapped_farm_token.token_nonce, wrapped_farm_token_nonce);
+            assert_eq!(
+                new_wrapped_farm_token.amount,
+                managed_biguint!(farm_token_amount)
+            );
+
+            new_wrapped_farm_token_nonce = new_wrapped_farm_token.token_nonce;
+        })
+        .assert_ok();
+
+    // positions in inactive farms are kept as they are
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &af_setup.farm_setup.farm_wrappers[FIRST_FARM_INDEX],
+            &rust_zero,
+            |sc| {
+                sc.state().set(State::Inactive);
+            },
+        )
+        .assert_ok();
+    b_mock.borrow_mut().set_block_nonce(20);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&keeper, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let user_id = 1;
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.claim_all_wrapped_farm_rewards(
+                &managed_address!(&first_user),
+                user_id,
+                &mut rew_wrapper,
+            );
+
+            assert!(rew_wrapper.locked_tokens.into_payments().is_empty());
+            let user_tokens = sc.get_user_wrapped_farm_tokens_view(managed_address!(&first_user));
+            assert_eq!(user_tokens.get(0).token_nonce, new_wrapped_farm_token_nonce);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user, &af_setup.auto_farm_wrapper, &rust_zero, |sc| {
+            let withdrawn_tokens = sc.withdraw_all_wrapped_farm_tokens_endpoint();
+            assert_eq!(withdrawn_tokens.len(), 1);
+            assert!(sc
+                .get_user_wrapped_farm_tokens_view(managed_address!(&first_user))
+                .is_empty());
+        })
+        .assert_ok();
+}
@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           29
+// Endpoints:                          117
 // Async Callback (empty):               1
-// Total number of exported functions:  32
+// Total number of exported functions: 120
 
 #![no_std]
 
@@ -20,23 +20,98 @@ multiversx_sc_wasm_adapter::endpoints! {
     (
         init => init
         upgrade => upgrade
-        changeProxyClaimAddress => change_proxy_claim_address
+        changeTreasuryAddress => change_treasury_address
         register => register
         withdrawAllAndUnregister => withdraw_all_and_unregister
         depositFarmTokens => deposit_farm_tokens
+        depositFarmTokensFor => deposit_farm_tokens_for
         withdrawAllFarmTokens => withdraw_all_farm_tokens_endpoint
         withdrawSpecificFarmTokens => withdraw_specific_farm_tokens_endpoint
         getUserFarmTokens => get_user_farm_tokens_view
+        addFarmMigration => add_farm_migration
+        removeFarmMigration => remove_farm_migration
+        getFarmMigration => get_farm_migration_view
+        getFarmMigrationProgress => get_farm_migration_progress
+        getUserMigratedFarmTokens => get_user_migrated_farm_tokens
         depositMetastakingTokens => deposit_metastaking_tokens
+        depositMetastakingTokensFor => deposit_metastaking_tokens_for
         withdrawAllMetastakingTokens => withdraw_all_metastaking_tokens_endpoint
         withdrawSpecificMetastakingTokens => withdraw_specific_metastaking_tokens_endpoint
         getUserMetastakingTokens => get_user_metastaking_tokens_view
+        depositLockedTokens => deposit_locked_tokens
+        withdrawLockedTokens => withdraw_locked_tokens_endpoint
+        getUserLockedTokens => get_user_locked_tokens_view
+        depositWrappedFarmTokens => deposit_wrapped_farm_tokens
+        withdrawAllWrappedFarmTokens => withdraw_all_wrapped_farm_tokens_endpoint
+        withdrawSpecificWrappedFarmTokens => withdraw_specific_wrapped_farm_tokens_endpoint
+        getUserWrappedFarmTokens => get_user_wrapped_farm_tokens_view
+        setLpCompoundMaxSlippage => set_lp_compound_max_slippage
+        getLpCompoundMaxSlippage => lp_compound_max_slippage
+        setKeeperCompensationConfig => set_keeper_compensation_config
+        setKeeperCompensationPair => set_keeper_compensation_pair
+        removeKeeperCompensationPair => remove_keeper_compensation_pair
+        getKeeperCompensationPair => get_keeper_compensation_pair_view
+        getKeeperCompensationWegldTokenId => keeper_compensation_wegld_token_id
+        getKeeperCompensationMaxSlippage => keeper_compensation_max_slippage
+        getKeeperEgldCompensation => keeper_egld_compensation
+        setEnergyCompoundLockEpochs => set_energy_compound_lock_epochs
+        getUserEnergyCompoundLockEpochs => get_user_energy_compound_lock_epochs_view
+        getLastEnergyCompound => get_last_energy_compound_view
         claimAllRewardsAndCompound => claim_all_rewards_and_compound
+        setMinGasForProcessing => set_min_gas_for_processing
+        processNextUsers => process_next_users
+        getProcessingProgress => get_processing_progress
+        getProcessingCursor => processing_cursor
+        getLastFullCycleEpoch => last_full_cycle_epoch
+        getMinGasForProcessing => min_gas_for_processing
+        setPositionCreationAddresses => set_position_creation_addresses
+        makeMeAPosition => make_me_a_position
         userClaimRewards => user_claim_rewards_endpoint
         getUserRewards => get_user_rewards_view
+        setPayoutThresholds => set_payout_thresholds
+        setPayoutFrequency => set_payout_frequency
+        getUserPayoutThresholds => get_user_payout_thresholds_view
+        getUserPayoutFrequency => get_user_payout_frequency_view
+        getUserLastPayoutEpoch => get_user_last_payout_epoch_view
+        setCompoundStrategy => set_compound_strategy
+        getUserCompoundStrategy => get_user_compound_strategy_view
+        addDelegate => add_delegate
+        removeDelegate => remove_delegate
+        getUserDelegates => get_user_delegates_view
+        transferPositions => transfer_positions
         claimFees => claim_fees
+        setFeeShares => set_fee_shares
+        setReferrer => set_referrer
+        claimKeeperFees => claim_keeper_fees
+        claimReferrerFees => claim_referrer_fees
+        getKeeperFees => get_keeper_fees_view
+        getReferrerFees => get_referrer_fees_view
+        getPendingKeeperCompensation => get_pending_keeper_compensation_view
+        getUserReferrer => get_user_referrer_view
+        setFeePercentage => set_fee_percentage
+        setFeeTiers => set_fee_tiers
+        setUserFeeOverride => set_user_fee_override
+        removeUserFeeOverride => remove_user_fee_override
+        getEffectiveFeePercentage => get_effective_fee_percentage_view
         getFeePercentage => fee_percentage
+        getFeeTiers => fee_tiers
+        getUserFeeOverride => user_fee_override
         getAccumulatedFees => accumulated_fees
+        getKeeperFeeShare => keeper_fee_share
+        getReferrerFeeShare => referrer_fee_share
+        getKeeperCompensationShare => keeper_compensation_share
+        setDepositCaps => set_deposit_caps
+        setMinDepositForAmount => set_min_deposit_for_amount
+        getDepositCaps => get_deposit_caps
+        getTotalDepositUsage => get_total_deposit_usage
+        getUserDepositUsage => get_user_deposit_usage
+        getMinDepositForAmount => min_deposit_for_amount
+        setInactiveFarmPolicy => set_inactive_farm_policy
+        getInactiveFarmPolicy => get_inactive_farm_policy_view
+        getUsersInInactiveFarms => get_users_in_inactive_farms
+        getUserPortfolio => get_user_portfolio
+        getUserLifetimeStats => get_user_lifetime_stats
+        getGlobalLifetimeStats => get_global_lifetime_stats
         setEnergyFactoryAddress => set_energy_factory_address
         getEnergyFactoryAddress => energy_factory_address
         addFarms => add_farms
@@ -49,6 +124,19 @@ multiversx_sc_wasm_adapter::endpoints! {
         getMetastakingForDualYieldToken => get_metastaking_for_dual_yield_token_view
         getMetastakingForLpFarmToken => get_metastaking_for_lp_farm_token
         getMetastakingConfig => get_metastaking_config
+        addProxyDexScs => add_proxy_dex_scs
+        removeProxyDexScs => remove_proxy_dex_scs
+        getProxyDexForWrappedFarmToken => get_proxy_dex_for_wrapped_farm_token_view
+        addKeepers => add_keepers
+        removeKeepers => remove_keepers
+        getKeepers => keepers
+        getTreasuryAddress => treasury_address
+        setContractState => set_contract_state
+        getContractState => contract_state
+        addAdmin => add_admin_endpoint
+        removeAdmin => remove_admin_endpoint
+        updateOwnerOrAdmin => update_owner_or_admin_endpoint
+        getPermissions => permissions
     )
 }
 
@@ -27,6 +27,13 @@ pub static DUAL_YIELD_TOKEN_ID_STORAGE_KEY: &[u8] = b"dualYieldTokenId";
 pub static STAKING_TOKEN_ID_STORAGE_KEY: &[u8] = b"stakingTokenId";
 pub static DIVISION_SAFETY_CONSTANT_STORAGE_KEY: &[u8] = b"division_safety_constant";
 pub static MINIMUM_FARMING_EPOCHS_STORAGE_KEY: &[u8] = b"minimum_farming_epochs";
+pub static PAIR_RESERVE_STORAGE_KEY: &[u8] = b"reserve";
+pub static PAIR_TOTAL_FEE_PERCENT_STORAGE_KEY: &[u8] = b"total_fee_percent";
+pub static PAIR_SPECIAL_FEE_PERCENT_STORAGE_KEY: &[u8] = b"special_fee_percent";
+pub static WRAPPED_FARM_TOKEN_ID_STORAGE_KEY: &[u8] = b"wrappedFarmTokenId";
+pub static REWARD_PER_SHARE_STORAGE_KEY: &[u8] = b"reward_per_share";
+pub static REWARD_TOKEN_ID_STORAGE_KEY: &[u8] = b"reward_token_id";
+pub static LP_TOKEN_SUPPLY_STORAGE_KEY: &[u8] = b"lp_token_supply";
 
 #[multiversx_sc::module]
 pub trait ReadExternalStorageModule {
@@ -169,4 +176,75 @@ pub trait ReadExternalStorageModule {
             StorageKey::new(MINIMUM_FARMING_EPOCHS_STORAGE_KEY),
         )
     }
+
+    fn get_pair_reserve_mapper(
+        &self,
+        sc_address: ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        let mut storage_key = StorageKey::new(PAIR_RESERVE_STORAGE_KEY);
+        storage_key.append_item(token_id);
+
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(sc_address, storage_key)
+    }
+
+    fn get_pair_total_fee_percent_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<u64, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(PAIR_TOTAL_FEE_PERCENT_STORAGE_KEY),
+        )
+    }
+
+    fn get_pair_special_fee_percent_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<u64, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(PAIR_SPECIAL_FEE_PERCENT_STORAGE_KEY),
+        )
+    }
+
+    fn get_wrapped_farm_token_id_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<TokenIdentifier, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(WRAPPED_FARM_TOKEN_ID_STORAGE_KEY),
+        )
+    }
+
+    fn get_reward_per_share_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(REWARD_PER_SHARE_STORAGE_KEY),
+        )
+    }
+
+    fn get_reward_token_id_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<TokenIdentifier, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(REWARD_TOKEN_ID_STORAGE_KEY),
+        )
+    }
+
+    fn get_lp_token_supply_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(LP_TOKEN_SUPPLY_STORAGE_KEY),
+        )
+    }
 }